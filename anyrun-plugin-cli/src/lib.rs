@@ -0,0 +1,124 @@
+//! Loads anyrun plugins through the same abi_stable root module anyrun uses,
+//! to query them from the terminal and from integration tests.
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use abi_stable::library::lib_header_from_path;
+use anyrun_interface::{HandleResult, Match, PluginInfo, PluginRef, PollResult};
+use serde_json::{Value, json};
+
+/// How long to wait for a plugin to produce its matches.
+const MATCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Plugin {
+    plugin: PluginRef,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let plugin = lib_header_from_path(path)
+            .and_then(|header| header.init_root_module::<PluginRef>())
+            .map_err(|e| format!("Failed to load plugin {}: {}", path.display(), e))?;
+        Ok(Plugin { plugin })
+    }
+
+    pub fn init(&self, config_dir: &str) {
+        (self.plugin.init())(config_dir.into());
+    }
+
+    pub fn info(&self) -> PluginInfo {
+        (self.plugin.info())()
+    }
+
+    /// Requests matches for `input` and waits until the plugin produces them.
+    pub fn get_matches(&self, input: &str) -> Result<Vec<Match>, String> {
+        let id = (self.plugin.get_matches())(input.into());
+        let started = Instant::now();
+
+        loop {
+            match (self.plugin.poll_matches())(id) {
+                PollResult::Ready(matches) => return Ok(matches.into_vec()),
+                PollResult::Cancelled => return Err("Plugin cancelled the request".into()),
+                PollResult::Pending => {}
+            }
+
+            if started.elapsed() > MATCH_TIMEOUT {
+                return Err(format!(
+                    "Plugin did not produce matches within {:?}",
+                    MATCH_TIMEOUT
+                ));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    pub fn handle(&self, selection: Match) -> HandleResult {
+        (self.plugin.handle_selection())(selection)
+    }
+}
+
+pub fn match_to_json(index: usize, m: &Match) -> Value {
+    json!({
+        "index": index,
+        "title": m.title.as_str(),
+        "description": m.description.as_ref().map(|d| d.as_str()).into_option(),
+        "icon": m.icon.as_ref().map(|i| i.as_str()).into_option(),
+        "use_pango": m.use_pango,
+        "id": m.id.as_ref().map(|id| *id).into_option(),
+    })
+}
+
+/// Kind of a handler result and the text it carries, if any.
+pub fn describe_result(result: &HandleResult) -> (&'static str, Option<String>) {
+    match result {
+        HandleResult::Close => ("close", None),
+        HandleResult::Refresh(exit) => ("refresh", Some(exit.to_string())),
+        HandleResult::Copy(bytes) => ("copy", Some(String::from_utf8_lossy(bytes).into())),
+        HandleResult::Stdout(bytes) => ("stdout", Some(String::from_utf8_lossy(bytes).into())),
+    }
+}
+
+pub fn result_to_json(result: &HandleResult) -> Value {
+    let (kind, value) = describe_result(result);
+    json!({ "result": kind, "value": value })
+}
+
+/// Formats matches as a plain text table.
+pub fn matches_to_table(matches: &[Match]) -> String {
+    let rows = matches
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            [
+                i.to_string(),
+                m.title.to_string(),
+                m.description
+                    .as_ref()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                m.icon.as_ref().map(|i| i.to_string()).unwrap_or_default(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let header = ["#", "title", "description", "icon"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.lines().next().unwrap_or("").chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell.lines().next().unwrap_or("")))
+            .collect::<Vec<_>>();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
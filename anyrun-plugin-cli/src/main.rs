@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyrun_plugin_cli::{Plugin, describe_result, match_to_json, matches_to_table, result_to_json};
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: anyrun-plugin-cli [OPTIONS] <PLUGIN> [INPUT]...
+
+Loads a plugin .so, prints its matches for INPUT and optionally handles one.
+
+Options:
+  --config-dir <DIR>  Directory passed to the plugin's init [default: ~/.config/anyrun]
+  --select <INDEX>    Call the handler with the match at INDEX
+  --dry-run           Print the commands and file writes of the selected match instead of performing them
+  --json              Print matches and the handler result as JSON
+  -h, --help          Print this help";
+
+struct Args {
+    plugin: PathBuf,
+    input: String,
+    config_dir: String,
+    select: Option<usize>,
+    dry_run: bool,
+    json: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut plugin = None;
+    let mut input = Vec::new();
+    let mut config_dir = None;
+    let mut select = None;
+    let mut dry_run = false;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config-dir" => {
+                config_dir = Some(args.next().ok_or("--config-dir needs a directory")?);
+            }
+            "--select" => {
+                let index = args.next().ok_or("--select needs an index")?;
+                let index = index
+                    .parse()
+                    .map_err(|_| format!("Invalid index '{}'", index))?;
+                select = Some(index);
+            }
+            "--dry-run" => dry_run = true,
+            "--json" => json = true,
+            _ if plugin.is_none() => plugin = Some(PathBuf::from(arg)),
+            _ => input.push(arg),
+        }
+    }
+
+    let config_dir = match config_dir {
+        Some(dir) => dir,
+        None => dirs::config_dir()
+            .ok_or("Could not locate config directory, use --config-dir")?
+            .join("anyrun")
+            .to_string_lossy()
+            .into_owned(),
+    };
+
+    Ok(Args {
+        plugin: plugin.ok_or(USAGE)?,
+        input: input.join(" "),
+        config_dir,
+        select,
+        dry_run,
+        json,
+    })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    if args.dry_run {
+        // SAFETY: no other threads exist yet, the plugin is loaded below.
+        unsafe { std::env::set_var(matcher::context::DRY_RUN_ENV, "1") };
+    }
+
+    let plugin = Plugin::load(&args.plugin)?;
+    plugin.init(&args.config_dir);
+    let info = plugin.info();
+    let matches = plugin.get_matches(&args.input)?;
+
+    let result = match args.select {
+        Some(index) => {
+            let selection = matches.get(index).cloned().ok_or_else(|| {
+                format!("No match at index {}, got {} matches", index, matches.len())
+            })?;
+            Some(plugin.handle(selection))
+        }
+        None => None,
+    };
+
+    if args.json {
+        let output = json!({
+            "plugin": { "name": info.name.as_str(), "icon": info.icon.as_str() },
+            "input": args.input,
+            "matches": matches
+                .iter()
+                .enumerate()
+                .map(|(i, m)| match_to_json(i, m))
+                .collect::<Vec<_>>(),
+            "handled": result.as_ref().map(result_to_json),
+        });
+        println!("{:#}", output);
+        return Ok(());
+    }
+
+    println!("{} ({})", info.name, info.icon);
+    print!("{}", matches_to_table(&matches));
+    if let Some(result) = result {
+        match describe_result(&result) {
+            (kind, Some(value)) => println!("=> {}: {}", kind, value),
+            (kind, None) => println!("=> {}", kind),
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if std::env::args().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let result = parse_args().and_then(run);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
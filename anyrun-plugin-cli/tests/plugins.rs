@@ -0,0 +1,258 @@
+//! Builds every plugin, loads it through the abi_stable root module anyrun
+//! uses and runs scripted queries against it. External programs are replaced
+//! by stub executables on `PATH`, and `HOME`/`XDG_STATE_HOME` point into a
+//! temporary directory, as does the todo file through `todo.ron`.
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use anyrun_interface::{HandleResult, Match};
+use anyrun_plugin_cli::{Plugin, describe_result};
+use tempfile::TempDir;
+
+const PLUGINS: &[&str] = &["timestamp", "todo", "vscode", "watson"];
+
+const WATSON_STUB: &str = r#"#!/bin/sh
+echo "watson $*" >> "$STUB_CALLS"
+case "$1" in
+    status) echo "Project anyrun [coding] started 3 hours ago (2025.11.06 14:00:00-0500)" ;;
+    tags) printf 'coding\nmeeting\n' ;;
+    log) echo '[{"project": "anyrun", "tags": ["coding"]}, {"project": "client-x", "tags": []}]' ;;
+esac
+"#;
+
+const CODE_STUB: &str = r#"#!/bin/sh
+echo "code $*" >> "$STUB_CALLS"
+"#;
+
+/// Temporary home, state and config directories plus stub executables,
+/// installed into the environment of the test process once.
+struct Env {
+    dir: TempDir,
+    plugins: HashMap<String, PathBuf>,
+}
+
+impl Env {
+    fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    fn config_dir(&self) -> String {
+        self.dir
+            .path()
+            .join("config")
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn calls(&self) -> String {
+        fs::read_to_string(self.dir.path().join("calls")).unwrap_or_default()
+    }
+
+    fn load(&self, name: &str) -> Plugin {
+        let plugin = Plugin::load(&self.plugins[name]).unwrap();
+        plugin.init(&self.config_dir());
+        plugin
+    }
+}
+
+fn env() -> MutexGuard<'static, Env> {
+    static ENV: OnceLock<Mutex<Env>> = OnceLock::new();
+    let env = ENV.get_or_init(|| {
+        let plugins = build_plugins();
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        for path in [&bin, &dir.path().join("home"), &dir.path().join("config")] {
+            fs::create_dir_all(path).unwrap();
+        }
+        write_stub(&bin.join("watson"), WATSON_STUB);
+        write_stub(&bin.join("code"), CODE_STUB);
+
+        let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+        // SAFETY: set once, before any plugin is loaded, while tests hold no other env references.
+        unsafe {
+            std::env::set_var("PATH", path);
+            std::env::set_var("HOME", dir.path().join("home"));
+            std::env::set_var("XDG_STATE_HOME", dir.path().join("state"));
+            std::env::set_var("STUB_CALLS", dir.path().join("calls"));
+        }
+
+        Mutex::new(Env { dir, plugins })
+    });
+    env.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_stub(path: &Path, script: &str) {
+    fs::write(path, script).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Builds the plugin crates and returns the path of each `.so`.
+fn build_plugins() -> HashMap<String, PathBuf> {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .current_dir(workspace)
+        .args(["build", "--lib", "--message-format=json"]);
+    for name in PLUGINS {
+        command.args(["-p", &format!("anyrun-{}", name)]);
+    }
+
+    let output = command.output().expect("failed to run cargo build");
+    assert!(
+        output.status.success(),
+        "building plugins failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut plugins = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let Some(name) = message["target"]["name"].as_str() else {
+            continue;
+        };
+        let Some(name) = name.strip_prefix("anyrun_") else {
+            continue;
+        };
+        for file in message["filenames"].as_array().into_iter().flatten() {
+            if let Some(file) = file.as_str().filter(|f| f.ends_with(".so")) {
+                plugins.insert(name.to_string(), PathBuf::from(file));
+            }
+        }
+    }
+
+    for name in PLUGINS {
+        assert!(plugins.contains_key(*name), "no .so built for {}", name);
+    }
+    plugins
+}
+
+fn titles(matches: &[Match]) -> Vec<String> {
+    matches.iter().map(|m| m.title.to_string()).collect()
+}
+
+fn select(plugin: &Plugin, input: &str, title: &str) -> HandleResult {
+    let matches = plugin.get_matches(input).unwrap();
+    let selection = matches
+        .iter()
+        .find(|m| m.title.as_str() == title)
+        .unwrap_or_else(|| {
+            panic!(
+                "no match '{}' for '{}' in {:?}",
+                title,
+                input,
+                titles(&matches)
+            )
+        })
+        .clone();
+    plugin.handle(selection)
+}
+
+#[test]
+fn test_info() {
+    let env = env();
+    let expected = [
+        ("timestamp", "Timestamp"),
+        ("todo", "Todo"),
+        ("vscode", "VSCode Workspace"),
+        ("watson", "Watson"),
+    ];
+    for (name, title) in expected {
+        let info = env.load(name).info();
+        assert_eq!(info.name.as_str(), title);
+        assert!(!info.icon.is_empty(), "{} has no icon", name);
+    }
+}
+
+#[test]
+fn test_ignores_other_prefixes() {
+    let env = env();
+    for name in PLUGINS {
+        let matches = env.load(name).get_matches(":unknown query").unwrap();
+        assert!(matches.is_empty(), "{} matched another prefix", name);
+    }
+}
+
+#[test]
+fn test_timestamp() {
+    let env = env();
+    let plugin = env.load("timestamp");
+
+    let matches = plugin.get_matches(":ts yyyy").unwrap();
+    assert_eq!(titles(&matches), ["YYYY_MM_DD", "YYYY_MM_DD_HH_MM_SS"]);
+
+    let result = select(&plugin, ":ts unix", "UNIX_TIMESTAMP");
+    let (kind, value) = describe_result(&result);
+    assert_eq!(kind, "copy");
+    assert!(value.unwrap().parse::<i64>().is_ok());
+}
+
+#[test]
+fn test_todo() {
+    let env = env();
+    let todos = env.home().join("todos.txt");
+    let config = Path::new(&env.config_dir()).join("todo.ron");
+    fs::write(config, format!("(file: {:?})", todos)).unwrap();
+    let plugin = env.load("todo");
+
+    let result = select(&plugin, ":todo add water plants", "add water plants");
+    assert_eq!(describe_result(&result).0, "close");
+    let matches = plugin.get_matches(":todo ls").unwrap();
+    assert_eq!(titles(&matches), ["water plants"]);
+
+    let result = select(&plugin, ":todo ls water", "water plants");
+    assert_eq!(describe_result(&result).0, "close");
+    let todos = fs::read_to_string(&todos).unwrap();
+    assert!(todos.starts_with("- [x] water plants"), "{}", todos);
+    let matches = plugin.get_matches(":todo ls").unwrap();
+    assert_eq!(titles(&matches), ["ls"]);
+}
+
+#[test]
+fn test_watson() {
+    let env = env();
+    let plugin = env.load("watson");
+
+    let matches = plugin.get_matches(":tt").unwrap();
+    assert_eq!(titles(&matches), ["stop anyrun", "start", "log", "report"]);
+
+    let matches = plugin.get_matches(":tt start client").unwrap();
+    assert!(titles(&matches).contains(&"client-x ".to_string()));
+
+    let result = select(&plugin, ":tt stop", "stop anyrun");
+    assert_eq!(describe_result(&result).0, "close");
+    assert!(env.calls().contains("watson stop"));
+}
+
+#[test]
+fn test_vscode() {
+    let env = env();
+    let state = env
+        .home()
+        .join(".config/Code/User/globalStorage/state.vscdb");
+    fs::create_dir_all(state.parent().unwrap()).unwrap();
+    let conn = rusqlite::Connection::open(&state).unwrap();
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ItemTable (key TEXT UNIQUE, value TEXT)",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT OR REPLACE INTO ItemTable VALUES ('history.recentlyOpenedPathsList', ?1)",
+        [r#"{"entries": [{"folderUri": "file:///work/anyrun-plugins"}]}"#],
+    )
+    .unwrap();
+
+    let plugin = env.load("vscode");
+    let matches = plugin.get_matches(":vs anyrun").unwrap();
+    assert_eq!(titles(&matches), ["anyrun-plugins"]);
+
+    let result = select(&plugin, ":vs anyrun", "anyrun-plugins");
+    assert_eq!(describe_result(&result).0, "close");
+    assert!(env.calls().contains("code /work/anyrun-plugins"));
+}
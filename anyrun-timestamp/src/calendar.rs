@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use anyrun_plugin::HandleResult;
+use chrono::{Datelike, Months, NaiveDate, NaiveTime, TimeZone, Weekday};
+use matcher::{Matcher, SimpleMatch, action::Action, clock::Clock};
+
+use crate::format::TimestampFormat;
+use crate::parse::parse_timestamp;
+
+/// Shows the grid of a month, followed by a match for each of its days.
+pub struct CalendarMatcher {
+    pub clock: Arc<dyn Clock>,
+    /// Format the day matches copy, the plugin's first configured format.
+    pub format: Option<TimestampFormat>,
+}
+
+impl Matcher for CalendarMatcher {
+    fn get_matches(&self, input: Vec<&str>) -> Vec<SimpleMatch> {
+        let today = self.clock.now().date_naive();
+        let Some(month) = self.parse_month(&input.join(" "), today) else {
+            return Vec::new();
+        };
+
+        // chrono can't tell where its last supported month ends
+        let Some(grid) = month_grid(month, today) else {
+            return Vec::new();
+        };
+        let grid = SimpleMatch::new(&grid, "x-office-calendar", "").with_pango(true);
+        let mut matches = vec![grid];
+        for date in month
+            .iter_days()
+            .take_while(|date| date.month() == month.month())
+        {
+            let mut title = date.format("%a %-d %b %Y").to_string();
+            if date == today {
+                title.push_str(" (today)");
+            }
+            let copy = Action::Copy(self.format_date(date));
+            matches.push(SimpleMatch::new(
+                &title,
+                "x-office-calendar",
+                &copy.to_string(),
+            ));
+        }
+        matches
+    }
+
+    fn handle(&self, _selection: SimpleMatch) -> HandleResult {
+        HandleResult::Refresh(false)
+    }
+}
+
+impl CalendarMatcher {
+    /// First day of the month given as `2026-02`, any date `:ts` reads, or
+    /// the current month if empty.
+    fn parse_month(&self, text: &str, today: NaiveDate) -> Option<NaiveDate> {
+        let date = if text.is_empty() {
+            today
+        } else {
+            NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d")
+                .ok()
+                .or_else(|| {
+                    let time = parse_timestamp(&*self.clock, text)?;
+                    Some(time.with_timezone(&self.clock.zone()).date_naive())
+                })?
+        };
+        date.with_day(1)
+    }
+
+    fn format_date(&self, date: NaiveDate) -> String {
+        let midnight = self
+            .clock
+            .zone()
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest();
+        match (&self.format, midnight) {
+            (Some(format), Some(midnight)) => format.format(&midnight),
+            _ => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Monospace Pango grid of the month starting at `first`, weeks starting on
+/// Monday, with `today` in bold and underlined.
+fn month_grid(first: NaiveDate, today: NaiveDate) -> Option<String> {
+    let mut grid = format!("<tt>{:^20}\nMo Tu We Th Fr Sa Su\n", first.format("%B %Y"));
+    let padding = first.weekday().num_days_from_monday() as usize;
+    grid.push_str(&"   ".repeat(padding));
+
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    for date in first.iter_days().take_while(|date| *date <= last) {
+        if date == today {
+            grid.push_str(&format!("<b><u>{:>2}</u></b>", date.day()));
+        } else {
+            grid.push_str(&format!("{:>2}", date.day()));
+        }
+        if date == last {
+            break;
+        }
+        grid.push(if date.weekday() == Weekday::Sun {
+            '\n'
+        } else {
+            ' '
+        });
+    }
+    grid.push_str("</tt>");
+    Some(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_month_grid() {
+        let grid = month_grid(date("2026-02-01"), date("2026-02-14")).unwrap();
+        assert_eq!(
+            grid,
+            [
+                "<tt>   February 2026    ",
+                "Mo Tu We Th Fr Sa Su",
+                "                   1",
+                " 2  3  4  5  6  7  8",
+                " 9 10 11 12 13 <b><u>14</u></b> 15",
+                "16 17 18 19 20 21 22",
+                "23 24 25 26 27 28</tt>",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            month_grid(NaiveDate::MAX.with_day(1).unwrap(), date("2026-02-14")),
+            None
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeDelta, Weekday};
+use matcher::{SimpleMatch, action::Action, clock::Clock};
+
+use crate::parse::parse_timestamp;
+
+/// Words that may separate the two dates, as in `diff today and friday`.
+const SEPARATORS: &[&str] = &["and", "to", "until"];
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Reads the two dates of `diff <a> <b>`, each in any format `:ts` accepts.
+/// Without a separator word, the first split where both sides are dates wins.
+pub fn parse_diff(
+    clock: &dyn Clock,
+    input: &[&str],
+) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let parse = |split: usize, skip: usize| {
+        let from = parse_timestamp(clock, &input[..split].join(" "))?;
+        let to = parse_timestamp(clock, &input[split + skip..].join(" "))?;
+        Some((from, to))
+    };
+
+    if let Some(split) = input.iter().position(|word| SEPARATORS.contains(word)) {
+        return parse(split, 1);
+    }
+    (1..input.len()).find_map(|split| parse(split, 0))
+}
+
+/// The time from `from` to `to` in days, business days, weeks and as an
+/// ISO 8601 duration. Each match copies its number.
+pub fn diff_matches(from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> Vec<SimpleMatch> {
+    let delta = *to - *from;
+    let days = delta.num_seconds() as f64 / SECONDS_PER_DAY;
+    let business_days = business_days(from.date_naive(), to.date_naive());
+
+    [
+        (format_number(days), "day"),
+        (business_days.to_string(), "business day"),
+        (format_number(days / 7.0), "week"),
+        (iso_duration(delta), ""),
+    ]
+    .into_iter()
+    .map(|(value, unit)| {
+        let plural = if unit.is_empty() || value.trim_start_matches('-') == "1" {
+            ""
+        } else {
+            "s"
+        };
+        let title = format!("{} {}{}", value, unit, plural);
+        let copy = Action::Copy(value);
+        SimpleMatch::new(title.trim(), "x-office-calendar", &copy.to_string())
+    })
+    .collect()
+}
+
+/// Weekdays from `from` until the day before `to`, negative if `to` is earlier.
+fn business_days(from: NaiveDate, to: NaiveDate) -> i64 {
+    if to < from {
+        return -business_days(to, from);
+    }
+    let days = (to - from).num_days();
+    let weeks = days / 7;
+    let rest = (0..days % 7)
+        .filter(|offset| {
+            let day = from + TimeDelta::days(weeks * 7 + offset);
+            !matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+        })
+        .count() as i64;
+    weeks * 5 + rest
+}
+
+/// Up to two decimals, without trailing zeros.
+fn format_number(value: f64) -> String {
+    let value = format!("{:.2}", value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    match value {
+        "-0" => "0".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Duration like `P12DT3H4M5S`. Days are the largest unit, since months and
+/// years vary in length.
+fn iso_duration(delta: TimeDelta) -> String {
+    let sign = if delta < TimeDelta::zero() { "-" } else { "" };
+    let seconds = delta.num_seconds().unsigned_abs();
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds / 3_600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+
+    let mut duration = format!("{}P", sign);
+    if days > 0 {
+        duration.push_str(&format!("{}D", days));
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || days == 0 {
+        duration.push('T');
+        for (value, unit) in [(hours, 'H'), (minutes, 'M'), (seconds, 'S')] {
+            if value > 0 {
+                duration.push_str(&format!("{}{}", value, unit));
+            }
+        }
+        if duration.ends_with('T') {
+            duration.push_str("0S");
+        }
+    }
+    duration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_business_days() {
+        // 2025-11-06 is a Thursday
+        assert_eq!(business_days(date("2025-11-06"), date("2025-11-06")), 0);
+        assert_eq!(business_days(date("2025-11-06"), date("2025-11-07")), 1);
+        assert_eq!(business_days(date("2025-11-06"), date("2025-11-10")), 2);
+        assert_eq!(business_days(date("2025-11-08"), date("2025-11-10")), 0);
+        assert_eq!(business_days(date("2025-11-06"), date("2025-11-20")), 10);
+        assert_eq!(business_days(date("2025-11-20"), date("2025-11-06")), -10);
+    }
+
+    #[test]
+    fn test_iso_duration() {
+        let duration = |seconds| iso_duration(TimeDelta::seconds(seconds));
+        assert_eq!(duration(0), "PT0S");
+        assert_eq!(duration(45 * 86_400), "P45D");
+        assert_eq!(
+            duration(12 * 86_400 + 3 * 3_600 + 4 * 60 + 5),
+            "P12DT3H4M5S"
+        );
+        assert_eq!(duration(90 * 60), "PT1H30M");
+        assert_eq!(duration(-86_400 - 1), "-P1DT1S");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(45.0), "45");
+        assert_eq!(format_number(6.428_571), "6.43");
+        assert_eq!(format_number(1.5), "1.5");
+        assert_eq!(format_number(-0.001), "0");
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{Datelike, Months, NaiveDate};
+use matcher::{SimpleMatch, action::Action};
+
+/// Calendar facts about `date` used for planning, each copying its value.
+pub fn fact_matches(date: NaiveDate) -> Vec<SimpleMatch> {
+    let week = date.iso_week();
+    let year_end = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap();
+
+    let mut facts = vec![
+        ("ISO week", format!("{}-W{:02}", week.year(), week.week())),
+        ("Week-year", week.year().to_string()),
+        ("Day of year", date.ordinal().to_string()),
+        ("Quarter", format!("Q{}", date.month0() / 3 + 1)),
+        ("Weekday", date.format("%A").to_string()),
+    ];
+    // The month after the last one chrono supports has no first day
+    if let Some(next_month) = date.with_day(1).unwrap().checked_add_months(Months::new(1)) {
+        let month_end = next_month.pred_opt().unwrap();
+        facts.push((
+            "Days left in month",
+            (month_end - date).num_days().to_string(),
+        ));
+    }
+    facts.push((
+        "Days left in year",
+        (year_end - date).num_days().to_string(),
+    ));
+
+    facts
+        .into_iter()
+        .map(|(fact, value)| {
+            let title = format!("{} {}", fact, value);
+            let copy = Action::Copy(value);
+            SimpleMatch::new(&title, "x-office-calendar", &copy.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(date: &str) -> Vec<String> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        fact_matches(date).into_iter().map(|m| m.title).collect()
+    }
+
+    #[test]
+    fn test_fact_matches() {
+        assert_eq!(
+            titles("2026-03-14"),
+            [
+                "ISO week 2026-W11",
+                "Week-year 2026",
+                "Day of year 73",
+                "Quarter Q1",
+                "Weekday Saturday",
+                "Days left in month 17",
+                "Days left in year 292",
+            ]
+        );
+        // The ISO week-year differs from the calendar year around new year
+        let titles = titles("2024-12-30");
+        assert_eq!(titles[0], "ISO week 2025-W01");
+        assert_eq!(titles[1], "Week-year 2025");
+        assert_eq!(titles[6], "Days left in year 1");
+
+        // There is no month after the last one chrono supports
+        let last_month = fact_matches(NaiveDate::MAX.with_day(1).unwrap());
+        assert_eq!(last_month.len(), 6);
+        assert_eq!(last_month[5].title, "Days left in year 30");
+    }
+}
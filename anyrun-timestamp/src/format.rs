@@ -0,0 +1,144 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeZone, format::StrftimeItems};
+use serde::{Deserialize, Deserializer};
+
+/// How a timestamp is written, configured as a strftime pattern or a keyword.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    Strftime(String),
+    Rfc3339,
+    Rfc2822,
+    UnixSeconds,
+    UnixMillis,
+    UnixMicros,
+    UnixNanos,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = match pattern {
+            "rfc3339" => Pattern::Rfc3339,
+            "rfc2822" => Pattern::Rfc2822,
+            "unix" => Pattern::UnixSeconds,
+            "unix_ms" => Pattern::UnixMillis,
+            "unix_us" => Pattern::UnixMicros,
+            "unix_ns" => Pattern::UnixNanos,
+            pattern => {
+                StrftimeItems::new(pattern)
+                    .parse()
+                    .map_err(|e| format!("invalid strftime pattern '{}': {}", pattern, e))?;
+                Pattern::Strftime(pattern.to_string())
+            }
+        };
+        Ok(pattern)
+    }
+}
+
+/// A named output format of the plugin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimestampFormat {
+    pub name: String,
+    pub pattern: Pattern,
+}
+
+impl TimestampFormat {
+    pub fn new(name: &str, pattern: &str) -> Result<Self, String> {
+        if name.trim().is_empty() {
+            return Err(format!("format '{}' has no name", pattern));
+        }
+        Ok(TimestampFormat {
+            name: name.to_string(),
+            pattern: Pattern::parse(pattern)?,
+        })
+    }
+
+    pub fn format<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        match &self.pattern {
+            Pattern::Strftime(pattern) => time.format(pattern).to_string(),
+            Pattern::Rfc3339 => time.to_rfc3339(),
+            Pattern::Rfc2822 => time.to_rfc2822(),
+            Pattern::UnixSeconds => time.timestamp().to_string(),
+            Pattern::UnixMillis => time.timestamp_millis().to_string(),
+            Pattern::UnixMicros => time.timestamp_micros().to_string(),
+            Pattern::UnixNanos => time
+                .timestamp_nanos_opt()
+                .map_or_else(|| "out of range".to_string(), |n| n.to_string()),
+        }
+    }
+}
+
+/// The formats offered when the configuration doesn't list any.
+pub fn default_formats() -> Vec<TimestampFormat> {
+    [
+        ("YYYY_MM_DD", "%Y_%m_%d"),
+        ("YYYY_MM_DD_HH_MM_SS", "%Y_%m_%d_%H_%M_%S"),
+        ("UNIX_TIMESTAMP", "unix"),
+    ]
+    .into_iter()
+    .map(|(name, pattern)| TimestampFormat::new(name, pattern).unwrap())
+    .collect()
+}
+
+#[derive(Deserialize)]
+struct FormatConfig {
+    name: String,
+    format: String,
+}
+
+/// Deserializes a list of `(name: "..", format: "..")`, skipping the invalid
+/// formats so that a typo in one pattern doesn't disable the others.
+pub fn deserialize_formats<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<TimestampFormat>, D::Error> {
+    let formats = Vec::<FormatConfig>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(
+            |config| match TimestampFormat::new(&config.name, &config.format) {
+                Ok(format) => Some(format),
+                Err(e) => {
+                    eprintln!("Skipping timestamp format '{}': {}", config.name, e);
+                    None
+                }
+            },
+        )
+        .collect();
+    Ok(formats)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = DateTime::parse_from_rfc3339("2025-11-06T17:04:05.123456789-05:00").unwrap();
+        let format = |pattern| TimestampFormat::new("test", pattern).unwrap().format(&time);
+
+        assert_eq!(format("%Y-%m-%dT%H:%M:%S%:z"), "2025-11-06T17:04:05-05:00");
+        assert_eq!(format("rfc3339"), "2025-11-06T17:04:05.123456789-05:00");
+        assert_eq!(format("rfc2822"), "Thu, 6 Nov 2025 17:04:05 -0500");
+        assert_eq!(format("unix"), "1762466645");
+        assert_eq!(format("unix_ms"), "1762466645123");
+        assert_eq!(format("unix_us"), "1762466645123456");
+        assert_eq!(format("unix_ns"), "1762466645123456789");
+
+        let utc = time.with_timezone(&Utc);
+        let fixed = time.with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        let iso = TimestampFormat::new("iso", "%FT%T%:z").unwrap();
+        assert_eq!(iso.format(&utc), "2025-11-06T22:04:05+00:00");
+        assert_eq!(iso.format(&fixed), "2025-11-06T23:04:05+01:00");
+    }
+
+    #[test]
+    fn test_invalid_formats() {
+        assert!(TimestampFormat::new("broken", "%Y-%Q").is_err());
+        assert!(TimestampFormat::new("dangling", "%Y-%").is_err());
+        assert!(TimestampFormat::new(" ", "%Y").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, TimeZone, Utc};
+use ulid::Ulid;
+use uuid::{NoContext, Timestamp, Uuid};
+
+/// Discord snowflakes count milliseconds from the start of 2015.
+const DISCORD_EPOCH_MS: i64 = 1_420_070_400_000;
+/// Twitter snowflakes count milliseconds from 2010-11-04T01:42:54.657Z.
+const TWITTER_EPOCH_MS: i64 = 1_288_834_974_657;
+/// Snowflakes keep worker and sequence numbers in the low 22 bits.
+const SNOWFLAKE_SHIFT: u32 = 22;
+
+/// A UUIDv7 for `time`, whose random bits differ on every call.
+pub fn generate_uuid7<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    let seconds = time.timestamp().max(0) as u64;
+    let timestamp = Timestamp::from_unix(NoContext, seconds, time.timestamp_subsec_nanos());
+    Uuid::new_v7(timestamp).to_string()
+}
+
+/// A ULID for `time`, whose random bits differ on every call.
+pub fn generate_ulid<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    let time = time.with_timezone(&Utc);
+    Ulid::from_datetime(time.into()).to_string()
+}
+
+/// Times embedded in a UUIDv7, ULID or snowflake, labelled with the kind of ID.
+/// A snowflake could come from Discord or Twitter, so both are returned unless
+/// one of them would lie after `now`.
+pub fn decode_id(text: &str, now: DateTime<Utc>) -> Vec<(&'static str, DateTime<Utc>)> {
+    let text = text.trim();
+    if let Ok(uuid) = Uuid::parse_str(text)
+        && uuid.get_version_num() == 7
+        && let Some(timestamp) = uuid.get_timestamp()
+    {
+        let (seconds, nanos) = timestamp.to_unix();
+        return DateTime::from_timestamp(seconds as i64, nanos)
+            .map(|time| vec![("UUIDv7", time)])
+            .unwrap_or_default();
+    }
+    if text.len() == ulid::ULID_LEN
+        && let Ok(ulid) = Ulid::from_string(&text.to_uppercase())
+    {
+        return DateTime::from_timestamp_millis(ulid.timestamp_ms() as i64)
+            .map(|time| vec![("ULID", time)])
+            .unwrap_or_default();
+    }
+
+    // Snowflakes of a few years' age have 17 to 19 digits
+    let Ok(snowflake) = text.parse::<u64>() else {
+        return Vec::new();
+    };
+    if !(17..=19).contains(&text.len()) {
+        return Vec::new();
+    }
+    let millis = (snowflake >> SNOWFLAKE_SHIFT) as i64;
+    [
+        ("Discord snowflake", DISCORD_EPOCH_MS),
+        ("Twitter snowflake", TWITTER_EPOCH_MS),
+    ]
+    .into_iter()
+    .filter_map(|(kind, epoch)| Some((kind, DateTime::from_timestamp_millis(epoch + millis)?)))
+    .filter(|(_, time)| *time <= now)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(text: &str) -> Vec<(&'static str, String)> {
+        let now = DateTime::parse_from_rfc3339("2025-11-06T17:00:00Z").unwrap();
+        decode_id(text, now.to_utc())
+            .into_iter()
+            .map(|(kind, time)| (kind, time.to_rfc3339()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let time = DateTime::parse_from_rfc3339("2025-11-06T17:04:05.123Z").unwrap();
+        let expected = vec![("UUIDv7", "2025-11-06T17:04:05.123+00:00".to_string())];
+        assert_eq!(decode(&generate_uuid7(&time)), expected);
+        assert_eq!(decode(&generate_uuid7(&time).to_uppercase()), expected);
+
+        let expected = vec![("ULID", "2025-11-06T17:04:05.123+00:00".to_string())];
+        assert_eq!(decode(&generate_ulid(&time)), expected);
+        assert_eq!(decode(&generate_ulid(&time).to_lowercase()), expected);
+        assert_ne!(generate_ulid(&time), generate_ulid(&time));
+    }
+
+    #[test]
+    fn test_snowflakes() {
+        // Example IDs from the Discord and Twitter documentation
+        assert_eq!(
+            decode("175928847299117063"),
+            [
+                (
+                    "Discord snowflake",
+                    "2016-04-30T11:18:25.796+00:00".to_string()
+                ),
+                (
+                    "Twitter snowflake",
+                    "2012-03-03T13:01:20.453+00:00".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            decode("1050118621198921728"),
+            [
+                (
+                    "Discord snowflake",
+                    "2022-12-07T18:36:29.554+00:00".to_string()
+                ),
+                (
+                    "Twitter snowflake",
+                    "2018-10-10T20:19:24.211+00:00".to_string()
+                ),
+            ]
+        );
+        // Snowflakes can't be from the future
+        assert_eq!(decode("9050118621198921728"), []);
+        assert_eq!(decode("1730822400"), []);
+
+        // Only 17 to 19 digits are read as snowflakes
+        assert_eq!(
+            decode("10000000000000000"),
+            [
+                (
+                    "Discord snowflake",
+                    "2015-01-28T14:16:25.791+00:00".to_string()
+                ),
+                (
+                    "Twitter snowflake",
+                    "2010-12-01T15:59:20.448+00:00".to_string()
+                ),
+            ]
+        );
+        assert_eq!(decode("9999999999999999"), []);
+        assert_eq!(decode("00000000000000000001"), []);
+    }
+
+    #[test]
+    fn test_other_ids() {
+        assert_eq!(decode("67e55044-10b1-426f-9247-bb680e5fe0c8"), []);
+        assert_eq!(decode("yyyy_mm_dd"), []);
+    }
+}
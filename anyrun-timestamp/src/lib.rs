@@ -1,12 +1,63 @@
+mod calendar;
+mod diff;
+mod facts;
+mod format;
+mod id;
+mod parse;
+mod snippets;
+mod timer;
+mod timestamp_matcher;
+mod zone;
 use abi_stable::std_types::{RString, RVec};
 use anyrun_plugin::{HandleResult, Match, PluginInfo, get_matches, handler, info, init};
-use chrono::Local;
-use matcher::{Matcher, NoopMatcher, SimpleMatch, matcher_static::StaticMatcher};
+use chrono_tz::Tz;
+use matcher::{
+    Matcher, NoopMatcher, SimpleMatch, action::Action, config::load_config, context::Context,
+    matcher_static::StaticMatcher, notifier::DEFAULT_NOTIFIER,
+};
+use serde::Deserialize;
+
+use crate::calendar::CalendarMatcher;
+use crate::facts::fact_matches;
+use crate::format::{TimestampFormat, default_formats, deserialize_formats};
+use crate::id::{generate_ulid, generate_uuid7};
+use crate::timer::Timers;
+use crate::timestamp_matcher::TimestampMatcher;
+use crate::zone::deserialize_zones;
 
 const PREFIX: &str = ":ts";
 
+/// Configuration read from `timestamp.ron` in the anyrun config directory.
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    /// Print the selected value to stdout instead of copying or executing it.
+    stdout: bool,
+    /// Formats offered for the current time, validated when loading.
+    #[serde(deserialize_with = "deserialize_formats")]
+    formats: Vec<TimestampFormat>,
+    /// Zones whose current time is always offered, looked up like in queries.
+    #[serde(deserialize_with = "deserialize_zones")]
+    zones: Vec<Tz>,
+    /// Command notifying when a timer elapses, `notify-send` by default.
+    notifier: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            stdout: false,
+            formats: default_formats(),
+            zones: Vec::new(),
+            notifier: None,
+        }
+    }
+}
+
 #[init]
-fn init(_config_dir: RString) {}
+fn init(config_dir: RString) -> Config {
+    load_config(&config_dir, "timestamp.ron")
+}
 
 #[info]
 fn info() -> PluginInfo {
@@ -17,7 +68,7 @@ fn info() -> PluginInfo {
 }
 
 #[get_matches]
-fn get_matches(input: RString) -> RVec<Match> {
+fn get_matches(input: RString, config: &Config) -> RVec<Match> {
     let input = input.trim().to_lowercase();
 
     if !input.starts_with(PREFIX) {
@@ -29,45 +80,479 @@ fn get_matches(input: RString) -> RVec<Match> {
         .split_ascii_whitespace()
         .collect::<Vec<_>>();
 
-    let matcher = get_matcher();
+    let matcher = get_matcher(&Context::system(), config);
     let matches = matcher.get_matches(input);
     let matches: Vec<Match> = matches.into_iter().map(|m| m.into()).collect();
     RVec::from(matches)
 }
 
 #[handler]
-fn handler(selection: Match) -> HandleResult {
-    let matcher = get_matcher();
+fn handler(selection: Match, config: &Config) -> HandleResult {
+    let matcher = get_matcher(&Context::system(), config);
     matcher.handle(selection.into())
 }
 
-fn get_matcher() -> Box<dyn Matcher> {
-    let now = Local::now();
-    let date = now.format("%Y_%m_%d").to_string();
-    let datetime = now.format("%Y_%m_%d_%H_%M_%S").to_string();
-    let unix = now.timestamp().to_string();
+fn get_matcher(context: &Context, config: &Config) -> Box<dyn Matcher> {
+    let now = context.clock.now();
 
-    let matcher = StaticMatcher::new()
-        .with_child(
-            SimpleMatch::new("YYYY_MM_DD", "x-office-calendar", &format!("copy|{}", date)),
+    let mut matcher = StaticMatcher::new().with_stdout(config.stdout);
+    for format in &config.formats {
+        // The description previews the value that selecting the match copies
+        let copy = Action::Copy(format.format(&now));
+        matcher = matcher.with_child(
+            SimpleMatch::new(&format.name, "x-office-calendar", &copy.to_string()),
+            Box::new(NoopMatcher),
+        );
+    }
+    for zone in &config.zones {
+        let time = now.with_timezone(zone).format("%Y-%m-%d %H:%M %Z");
+        let copy = Action::Copy(time.to_string());
+        matcher = matcher.with_child(
+            SimpleMatch::new(zone.name(), "preferences-system-time", &copy.to_string()),
+            Box::new(NoopMatcher),
+        );
+    }
+    matcher = matcher.with_child(
+        SimpleMatch::new("cal", "x-office-calendar", ""),
+        Box::new(CalendarMatcher {
+            clock: context.clock.clone(),
+            format: config.formats.first().cloned(),
+        }),
+    );
+    for (name, id) in [
+        ("UUID7", generate_uuid7(&now)),
+        ("ULID", generate_ulid(&now)),
+    ] {
+        let copy = Action::Copy(id);
+        matcher = matcher.with_child(
+            SimpleMatch::new(name, "x-office-document", &copy.to_string()),
             Box::new(NoopMatcher),
+        );
+    }
+    for fact in fact_matches(now.date_naive()) {
+        matcher = matcher.with_child(fact, Box::new(NoopMatcher));
+    }
+
+    let notifier = config.notifier.as_deref().unwrap_or(DEFAULT_NOTIFIER);
+    Box::new(TimestampMatcher {
+        root: matcher,
+        formats: config.formats.clone(),
+        clock: context.clock.clone(),
+        timers: Timers::for_plugin(context, notifier),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use matcher::clock::Clock;
+    use matcher::testing::{
+        Harness, assert_closed, assert_copied, assert_refreshed, assert_titles, find, select,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_formats() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let matcher = get_matcher(&harness.context(), &Config::default());
+
+        assert_titles(&*matcher, "yyyy", &["YYYY_MM_DD", "YYYY_MM_DD_HH_MM_SS"]);
+        assert_copied(&select(&*matcher, "", "YYYY_MM_DD"), "2025_11_06");
+        assert_copied(
+            &select(&*matcher, "", "YYYY_MM_DD_HH_MM_SS"),
+            "2025_11_06_17_04_05",
+        );
+        assert_copied(
+            &select(&*matcher, "unix", "UNIX_TIMESTAMP"),
+            &harness.clock().now().timestamp().to_string(),
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let config: Config = ron::from_str(
+            r#"(formats: [
+                (name: "ISO 8601", format: "%Y-%m-%dT%H:%M:%S%:z"),
+                (name: "Millis", format: "unix_ms"),
+            ])"#,
         )
-        .with_child(
-            SimpleMatch::new(
-                "YYYY_MM_DD_HH_MM_SS",
-                "x-office-calendar",
-                &format!("copy|{}", datetime),
+        .unwrap();
+        let matcher = get_matcher(&harness.context(), &config);
+
+        assert_titles(
+            &*matcher,
+            "2024-11-05 12:00",
+            &[
+                "ISO 8601 (local)",
+                "ISO 8601 (UTC)",
+                "Millis (local)",
+                "Millis (UTC)",
+                "ISO week 2024-W45",
+                "Week-year 2024",
+                "Day of year 310",
+                "Quarter Q4",
+                "Weekday Tuesday",
+                "Days left in month 25",
+                "Days left in year 56",
+            ],
+        );
+        for input in [
+            "1730822400",
+            "1730822400000",
+            "2024-11-05t16:00:00z",
+            "2024-11-05t17:00:00+01:00",
+            "tue, 5 nov 2024 16:00:00 +0000",
+        ] {
+            assert_copied(
+                &select(&*matcher, input, "ISO 8601 (UTC)"),
+                "2024-11-05T16:00:00+00:00",
+            );
+            assert_copied(&select(&*matcher, input, "Millis (local)"), "1730822400000");
+        }
+
+        // Date expressions are evaluated against the clock
+        for (input, expected) in [
+            ("tomorrow", "2025-11-07T00:00:00"),
+            ("next friday 14:00", "2025-11-07T14:00:00"),
+            ("+3d", "2025-11-09T17:04:05"),
+            ("2 weeks ago", "2025-10-23T17:04:05"),
+            ("end of month", "2025-11-30T23:59:59"),
+        ] {
+            let converted = find(&*matcher, input, "ISO 8601 (local)");
+            assert!(
+                converted.description.contains(expected),
+                "{} converted to {}",
+                input,
+                converted.description
+            );
+        }
+
+        // Anything else filters the formats for the current time
+        assert_titles(&*matcher, "iso", &["ISO 8601", "ISO week 2025-W45"]);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let matcher = get_matcher(&harness.context(), &Config::default());
+
+        assert_titles(
+            &*matcher,
+            "diff 2025-11-06 2025-12-24",
+            &["48 days", "34 business days", "6.86 weeks", "P48D"],
+        );
+        assert_titles(
+            &*matcher,
+            "diff 2025-11-06t09:00:00z and 2025-11-07t10:30:00z",
+            &["1.06 days", "1 business day", "0.15 weeks", "P1DT1H30M"],
+        );
+        assert_copied(&select(&*matcher, "diff today until friday", "1 day"), "1");
+        assert_copied(
+            &select(&*matcher, "diff now end of day", "PT6H55M54S"),
+            "PT6H55M54S",
+        );
+        assert_titles(&*matcher, "diff 2025-11-06", &[]);
+
+        assert_copied(
+            &select(&*matcher, "2025-11-06 + 45d", "YYYY_MM_DD (local)"),
+            "2025_12_21",
+        );
+    }
+
+    #[test]
+    fn test_calendar_facts() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let matcher = get_matcher(&harness.context(), &Config::default());
+
+        assert_titles(
+            &*matcher,
+            "week",
+            &["ISO week 2025-W45", "Week-year 2025", "Weekday Thursday"],
+        );
+        assert_copied(
+            &select(&*matcher, "iso week", "ISO week 2025-W45"),
+            "2025-W45",
+        );
+        assert_copied(&select(&*matcher, "quarter", "Quarter Q4"), "Q4");
+        assert_copied(
+            &select(&*matcher, "days left", "Days left in year 55"),
+            "55",
+        );
+
+        // Facts of a given date follow its formats
+        assert_copied(
+            &select(&*matcher, "2026-03-14", "ISO week 2026-W11"),
+            "2026-W11",
+        );
+        assert_copied(
+            &select(&*matcher, "next monday", "Weekday Monday"),
+            "Monday",
+        );
+    }
+
+    #[test]
+    fn test_ids() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let config: Config =
+            ron::from_str(r#"(formats: [(name: "ISO 8601", format: "%Y-%m-%dT%H:%M:%S%:z")])"#)
+                .unwrap();
+        let matcher = get_matcher(&harness.context(), &config);
+        let utc = harness.clock().now().to_utc().to_rfc3339();
+
+        for (name, length) in [("UUID7", 36), ("ULID", 26)] {
+            let id = find(&*matcher, &name.to_lowercase(), name);
+            let id = id.description.strip_prefix("copy|").unwrap();
+            assert_eq!(id.len(), length);
+            assert_copied(&select(&*matcher, id, "ISO 8601 (UTC)"), &utc);
+        }
+
+        // Numbers which could be an epoch or a snowflake list every reading
+        let headers = matcher
+            .get_matches(vec!["1050118621198921728"])
+            .into_iter()
+            .filter(|m| m.description.is_empty())
+            .map(|m| m.title)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headers,
+            ["Timestamp", "Discord snowflake", "Twitter snowflake"]
+        );
+        assert_copied(
+            &select(&*matcher, "1050118621198921728", "ISO week 2018-W41"),
+            "2018-W41",
+        );
+    }
+
+    #[test]
+    fn test_snippets() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let matcher = get_matcher(&harness.context(), &Config::default());
+
+        let titles = |query| {
+            matcher
+                .get_matches(vec!["snippets", query])
+                .into_iter()
+                .map(|m| m.title)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            titles("2024-11-05t16:00:00z")
+                .iter()
+                .filter(|title| !title.contains(": "))
+                .collect::<Vec<_>>(),
+            ["Discord", "Slack"]
+        );
+        assert_eq!(titles("someday"), Vec::<String>::new());
+
+        assert_copied(
+            &select(
+                &*matcher,
+                "snippets 2024-11-05t16:00:00z",
+                "Relative: 1 year ago",
             ),
-            Box::new(NoopMatcher),
+            "<t:1730822400:R>",
+        );
+        assert_copied(
+            &select(&*matcher, "snippets 1730822400", "Relative: 1 year ago"),
+            "<t:1730822400:R>",
+        );
+        assert_copied(
+            &select(&*matcher, "snippet +2h", "{ago}: in 2 hours"),
+            &format!(
+                "<!date^{}^{{ago}}|{} UTC>",
+                harness.clock().now().timestamp() + 7_200,
+                (harness.clock().now() + chrono::Duration::hours(2))
+                    .to_utc()
+                    .format("%Y-%m-%d %H:%M"),
+            ),
+        );
+        let now = harness.clock().now().timestamp();
+        assert_copied(
+            &select(&*matcher, "snippets", "Relative: now"),
+            &format!("<t:{}:R>", now),
+        );
+        let short_time = harness
+            .clock()
+            .now()
+            .format("Short time: %H:%M")
+            .to_string();
+        assert_copied(
+            &select(&*matcher, "snippets", &short_time),
+            &format!("<t:{}:t>", now),
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let matcher = get_matcher(&harness.context(), &Config::default());
+
+        let matches = matcher.get_matches(vec!["cal"]);
+        assert!(matches[0].use_pango);
+        assert!(matches[0].title.contains("November 2025"));
+        assert!(matches[0].title.contains("<b><u> 6</u></b>"));
+        assert_eq!(matches.len(), 31);
+        assert_eq!(matches[6].title, "Thu 6 Nov 2025 (today)");
+
+        let matches = matcher.get_matches(vec!["cal", "2026-02"]);
+        assert!(matches[0].title.contains("February 2026"));
+        assert!(!matches[0].title.contains("<b>"));
+        assert_eq!(matches.len(), 29);
+        assert_copied(
+            &select(&*matcher, "cal 2026-02", "Sat 14 Feb 2026"),
+            "2026_02_14",
+        );
+        assert_copied(
+            &select(&*matcher, "cal next month", "Mon 1 Dec 2025"),
+            "2025_12_01",
+        );
+        assert_titles(&*matcher, "cal someday", &["cal"]);
+        assert_titles(&*matcher, "cal +262142-12", &["cal"]);
+    }
+
+    #[test]
+    fn test_zones() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let config: Config = ron::from_str(
+            r#"(
+                formats: [(name: "ISO 8601", format: "%Y-%m-%dT%H:%M:%S%:z")],
+                zones: ["Tokyo", "Atlantis", "utc"],
+            )"#,
         )
-        .with_child(
-            SimpleMatch::new(
-                "UNIX_TIMESTAMP",
-                "x-office-calendar",
-                &format!("copy|{}", unix),
+        .unwrap();
+        let matcher = get_matcher(&harness.context(), &config);
+
+        assert_titles(&*matcher, "zone", &[]);
+        assert_titles(&*matcher, "tokyo", &["Asia/Tokyo"]);
+        let utc = harness.clock().now().with_timezone(&chrono::Utc);
+        assert_copied(
+            &select(&*matcher, "utc", "UTC"),
+            &utc.format("%Y-%m-%d %H:%M UTC").to_string(),
+        );
+
+        assert_titles(
+            &*matcher,
+            "2024-11-05t16:00:00z in tokyo",
+            &["01:00 JST, Wed 6 Nov", "ISO 8601 (Asia/Tokyo)"],
+        );
+        assert_copied(
+            &select(
+                &*matcher,
+                "2024-11-05 16:00 utc to new york",
+                "ISO 8601 (America/New_York)",
             ),
-            Box::new(NoopMatcher),
+            "2024-11-05T11:00:00-05:00",
+        );
+        let tokyo = harness.clock().now().with_timezone(&chrono_tz::Asia::Tokyo);
+        assert_copied(
+            &select(&*matcher, "now in asia/tokyo", "ISO 8601 (Asia/Tokyo)"),
+            &tokyo.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+        );
+        // Times of day are converted on the current day
+        let converted = find(&*matcher, "15:00 utc in tokyo", "ISO 8601 (Asia/Tokyo)");
+        assert!(converted.description.contains("T00:00:00+09:00"));
+        let converted = find(&*matcher, "9am pst to cet", "ISO 8601 (Europe/Berlin)");
+        assert!(converted.description.contains("T18:00:00+01:00"));
+
+        assert_titles(&*matcher, "15:00 in atlantis", &[]);
+    }
+
+    #[test]
+    fn test_clock_zone() {
+        let harness = Harness::new()
+            .with_time("2025-11-06 17:04:05")
+            .with_zone(chrono_tz::America::New_York);
+        let config: Config =
+            ron::from_str(r#"(formats: [(name: "ISO 8601", format: "%Y-%m-%dT%H:%M:%S%:z")])"#)
+                .unwrap();
+        let matcher = get_matcher(&harness.context(), &config);
+
+        assert_copied(
+            &select(&*matcher, "", "ISO 8601"),
+            "2025-11-06T17:04:05-05:00",
+        );
+        assert_copied(
+            &select(&*matcher, "1730822400", "ISO 8601 (local)"),
+            "2024-11-05T11:00:00-05:00",
+        );
+        assert_copied(
+            &select(&*matcher, "2025-07-01 09:30", "ISO 8601 (UTC)"),
+            "2025-07-01T13:30:00+00:00",
+        );
+        assert_titles(
+            &*matcher,
+            "15:00 in tokyo",
+            &["05:00 JST, Fri 7 Nov", "ISO 8601 (Asia/Tokyo)"],
         );
+    }
+
+    #[test]
+    fn test_configured_formats() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let config: Config = ron::from_str(
+            r#"(formats: [
+                (name: "ISO 8601", format: "%Y-%m-%dT%H:%M:%S"),
+                (name: "Broken", format: "%Y-%Q"),
+                (name: "Millis", format: "unix_ms"),
+            ])"#,
+        )
+        .unwrap();
+        let matcher = get_matcher(&harness.context(), &config);
+
+        assert_titles(&*matcher, "broken", &[]);
+        assert_copied(&select(&*matcher, "iso", "ISO 8601"), "2025-11-06T17:04:05");
+        assert_copied(
+            &select(&*matcher, "millis", "Millis"),
+            &harness.clock().now().timestamp_millis().to_string(),
+        );
+    }
 
-    Box::new(matcher)
+    #[test]
+    fn test_timers() {
+        let harness = Harness::new()
+            .with_time("2025-11-06 17:04:05")
+            .with_command("systemd-run", "")
+            .with_command("systemctl", "");
+        let config = Config {
+            notifier: Some("stub-notify".into()),
+            ..Config::default()
+        };
+        let matcher = get_matcher(&harness.context(), &config);
+
+        assert_titles(&*matcher, "timers", &["No active timers"]);
+        assert_titles(&*matcher, "timer 25m tea", &["tea at 17:29"]);
+        assert_titles(&*matcher, "timer 1h30m", &["Timer at 18:34"]);
+        // Times of day which have passed are tomorrow
+        assert_titles(
+            &*matcher,
+            "at 14:55 standup",
+            &["standup at Fri 7 Nov 14:55"],
+        );
+        assert_titles(&*matcher, "at 9pm", &["Alarm at 21:00"]);
+        assert_titles(&*matcher, "at yesterday", &[]);
+
+        assert_closed(&select(&*matcher, "timer 25m tea", "tea at 17:29"));
+        let calls = harness.calls();
+        let call = calls.last().unwrap();
+        assert!(call.starts_with("systemd-run --user --collect --unit=anyrun-timer-"));
+        assert!(call.contains("--on-calendar='2025-11-06 17:29:05 UTC'"));
+        assert!(call.contains("stub-notify"));
+        assert!(call.contains("tea"));
+        assert_closed(&select(
+            &*matcher,
+            "at 14:55 standup",
+            "standup at Fri 7 Nov 14:55",
+        ));
+
+        let matcher = get_matcher(&harness.context(), &config);
+        assert_titles(
+            &*matcher,
+            "timers",
+            &["Cancel tea at 17:29", "Cancel standup at Fri 7 Nov 14:55"],
+        );
+        assert_refreshed(&select(&*matcher, "timers", "Cancel tea at 17:29"));
+        harness.assert_ran("systemctl --user stop anyrun-timer-");
+        assert_titles(&*matcher, "timers", &["Cancel standup at Fri 7 Nov 14:55"]);
+    }
 }
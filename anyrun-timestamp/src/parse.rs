@@ -0,0 +1,288 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use matcher::{
+    clock::Clock,
+    date_expression::{apply_offset, parse_date_expression},
+};
+
+/// Formats of timestamps without an offset, as found in logs. They are read
+/// in the zone given to `parse_timestamp_in`, the clock's zone by default.
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%d/%b/%Y:%H:%M:%S",
+];
+
+/// Formats of timestamps with an offset, besides RFC 3339 and RFC 2822.
+const OFFSET_FORMATS: &[&str] = &[
+    // Apache and nginx access logs
+    "%d/%b/%Y:%H:%M:%S %z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+];
+
+/// Reads a pasted timestamp: an epoch in seconds, milliseconds, microseconds or
+/// nanoseconds, RFC 3339, RFC 2822 or a common log format, or else a date
+/// expression like `tomorrow 9am`. Input may be lowercased, as plugins
+/// receive it.
+pub fn parse_timestamp(clock: &dyn Clock, text: &str) -> Option<DateTime<FixedOffset>> {
+    parse_timestamp_in(clock, text, &clock.zone())
+}
+
+/// Like `parse_timestamp`, but times without an offset are read in `zone`.
+pub fn parse_timestamp_in<Tz: TimeZone>(
+    clock: &dyn Clock,
+    text: &str,
+    zone: &Tz,
+) -> Option<DateTime<FixedOffset>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    parse_arithmetic(clock, text, zone).or_else(|| parse_base(clock, text, zone))
+}
+
+/// Reads a timestamp without offsets applied to it.
+fn parse_base<Tz: TimeZone>(
+    clock: &dyn Clock,
+    text: &str,
+    zone: &Tz,
+) -> Option<DateTime<FixedOffset>> {
+    let text = text.trim();
+    if text.bytes().all(|b| b.is_ascii_digit()) {
+        return parse_epoch(text);
+    }
+
+    // Log formats separate milliseconds with a comma, e.g. log4j
+    let expression = text;
+    let text = text.to_uppercase();
+    let text = match text.rsplit_once(',') {
+        Some((time, millis)) if millis.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{}.{}", time, millis)
+        }
+        _ => text,
+    };
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(&text) {
+        return Some(time);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc2822(&text) {
+        return Some(time);
+    }
+    for format in OFFSET_FORMATS {
+        if let Ok(time) = DateTime::parse_from_str(&text, format) {
+            return Some(time);
+        }
+    }
+
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&text, format).ok())
+        .or_else(|| parse_syslog(clock, zone, &text))
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()?;
+            date.and_hms_opt(0, 0, 0)
+        });
+    let time = match naive {
+        Some(naive) => zone.from_local_datetime(&naive).earliest()?,
+        None => parse_date_expression(clock, expression, zone)?,
+    };
+    Some(time.fixed_offset())
+}
+
+/// Reads a timestamp followed by offsets, like `2025-01-01 + 45d - 2h`. The
+/// base is the words before the first offset of a chain that applies, and is
+/// parsed once, so inputs full of signs stay cheap.
+fn parse_arithmetic<Tz: TimeZone>(
+    clock: &dyn Clock,
+    text: &str,
+    zone: &Tz,
+) -> Option<DateTime<FixedOffset>> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    (1..words.len())
+        .filter(|&i| words[i].starts_with(['+', '-']))
+        .find_map(|i| {
+            let offsets = split_offsets(&words[i..]);
+            // Check the offsets on any time before parsing the base
+            let epoch = DateTime::UNIX_EPOCH.fixed_offset();
+            offsets
+                .iter()
+                .try_fold(epoch, |time, offset| apply_offset(&time, offset))?;
+
+            let base = parse_base(clock, &words[..i].join(" "), zone)?;
+            offsets
+                .iter()
+                .try_fold(base, |time, offset| apply_offset(&time, offset))
+        })
+}
+
+/// Splits words into offsets, each starting at a word with a sign.
+fn split_offsets(words: &[&str]) -> Vec<String> {
+    let mut offsets: Vec<String> = Vec::new();
+    for word in words {
+        match offsets.last_mut() {
+            Some(offset) if !word.starts_with(['+', '-']) => {
+                offset.push(' ');
+                offset.push_str(word);
+            }
+            _ => offsets.push(word.to_string()),
+        }
+    }
+    offsets
+}
+
+/// Reads an epoch, guessing its unit from the number of digits. Seconds are
+/// assumed up to 11 digits, which covers the years until 5138.
+fn parse_epoch(text: &str) -> Option<DateTime<FixedOffset>> {
+    let value = text.parse::<i64>().ok()?;
+    let time = match text.len() {
+        0..=11 => DateTime::from_timestamp(value, 0)?,
+        12..=14 => DateTime::from_timestamp_millis(value)?,
+        15..=17 => DateTime::from_timestamp_micros(value)?,
+        18..=19 => DateTime::from_timestamp_nanos(value),
+        _ => return None,
+    };
+    Some(time.fixed_offset())
+}
+
+/// Reads syslog timestamps like `Nov 6 17:00:00`, which have no year. The
+/// most recent such time is assumed.
+fn parse_syslog<Tz: TimeZone>(clock: &dyn Clock, zone: &Tz, text: &str) -> Option<NaiveDateTime> {
+    let now = clock.now().with_timezone(zone).naive_local();
+    let parse = |year: i32| {
+        NaiveDateTime::parse_from_str(&format!("{} {}", year, text), "%Y %b %e %H:%M:%S").ok()
+    };
+    let time = parse(now.year())?;
+    if time > now {
+        return parse(now.year() - 1);
+    }
+    Some(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use matcher::clock::{FakeClock, Zone};
+
+    use super::*;
+
+    /// Clock in a zone with daylight saving time, so offsets are checked.
+    fn clock() -> FakeClock {
+        let clock = FakeClock::at("2025-11-06 17:00:00");
+        clock.set_zone(Zone::Named(chrono_tz::Europe::Berlin));
+        clock
+    }
+
+    fn parse(text: &str) -> Option<String> {
+        parse_timestamp(&clock(), text).map(|time| time.to_rfc3339())
+    }
+
+    /// A time in the zone of the clock.
+    fn local(time: &str) -> Option<String> {
+        let naive = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+        let time = clock().zone().from_local_datetime(&naive).unwrap();
+        Some(time.fixed_offset().to_rfc3339())
+    }
+
+    #[test]
+    fn test_epochs() {
+        let expected = Some("2024-11-05T16:00:00+00:00".to_string());
+        assert_eq!(parse("1730822400"), expected);
+        assert_eq!(
+            parse("1730822400123"),
+            Some("2024-11-05T16:00:00.123+00:00".to_string())
+        );
+        assert_eq!(
+            parse("1730822400123456"),
+            Some("2024-11-05T16:00:00.123456+00:00".to_string())
+        );
+        assert_eq!(
+            parse("1730822400123456789"),
+            Some("2024-11-05T16:00:00.123456789+00:00".to_string())
+        );
+        assert_eq!(parse("17308224001234567890"), None);
+    }
+
+    #[test]
+    fn test_formats_with_offset() {
+        let expected = Some("2025-11-06T17:00:00+00:00".to_string());
+        assert_eq!(parse("2025-11-06t17:00:00z"), expected);
+        assert_eq!(parse("thu, 6 nov 2025 17:00:00 +0000"), expected);
+        assert_eq!(parse("06/nov/2025:17:00:00 +0000"), expected);
+        assert_eq!(
+            parse("2025-11-06 18:00:00+01:00"),
+            Some("2025-11-06T18:00:00+01:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_local_formats() {
+        assert_eq!(parse("2025-11-06 17:00:00"), local("2025-11-06 17:00:00"));
+        assert_eq!(
+            parse("2025-11-06 17:00:00,123"),
+            local("2025-11-06 17:00:00.123")
+        );
+        assert_eq!(parse("2025-11-06"), local("2025-11-06 00:00:00"));
+        // Syslog times are in the past year
+        assert_eq!(parse("nov 6 16:00:00"), local("2025-11-06 16:00:00"));
+        assert_eq!(parse("dec 24 08:00:00"), local("2024-12-24 08:00:00"));
+        assert_eq!(parse("someday"), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            parse("2025-01-01t12:00:00z + 45d"),
+            Some("2025-02-15T12:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            parse("1730822400 -1h30m"),
+            Some("2024-11-05T14:30:00+00:00".to_string())
+        );
+        assert_eq!(parse("2025-01-01 + 2 weeks"), local("2025-01-15 00:00:00"));
+        assert_eq!(
+            parse("thu, 6 nov 2025 17:00:00 +0000 + 1d"),
+            Some("2025-11-07T17:00:00+00:00".to_string())
+        );
+        assert_eq!(parse("2025-01-01 + soon"), None);
+        assert_eq!(
+            parse("2025-01-01t12:00:00z + 1d -2h"),
+            Some("2025-01-02T10:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_many_signs() {
+        let start = std::time::Instant::now();
+        let text = ["x"].into_iter().chain(["-"; 40]).collect::<Vec<_>>();
+        assert_eq!(parse(&text.join(" ")), None);
+        let text = ["x"].into_iter().chain(["-1d"; 40]).collect::<Vec<_>>();
+        assert_eq!(parse(&text.join(" ")), None);
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_date_expressions() {
+        let clock = FakeClock::at("2025-11-06 17:00:00");
+        let parse = |text| parse_timestamp_in(&clock, text, &Utc).map(|time| time.to_rfc3339());
+        let today = |time: &str| {
+            Some(format!(
+                "{}T{}+00:00",
+                clock.now().with_timezone(&Utc).date_naive(),
+                time
+            ))
+        };
+
+        assert_eq!(parse("15:00"), today("15:00:00"));
+        assert_eq!(parse("15:00:30"), today("15:00:30"));
+        assert_eq!(parse("9am"), today("09:00:00"));
+        assert_eq!(parse("9:30 pm"), today("21:30:00"));
+        let later = clock.now() + chrono::Duration::hours(2);
+        assert_eq!(parse("+2h"), Some(later.with_timezone(&Utc).to_rfc3339()));
+        assert_eq!(
+            parse("now"),
+            Some(clock.now().with_timezone(&Utc).to_rfc3339())
+        );
+        assert_eq!(parse("25:00"), None);
+    }
+}
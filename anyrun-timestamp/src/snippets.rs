@@ -0,0 +1,114 @@
+use chrono::{DateTime, TimeZone};
+use matcher::{
+    SimpleMatch,
+    action::Action,
+    clock::Zone,
+    group::{Group, Groups},
+};
+
+const DISCORD: &str = "Discord";
+const SLACK: &str = "Slack";
+
+/// Discord timestamp styles, with a strftime pattern approximating how Discord
+/// renders them. `R` is rendered relative to now instead.
+const DISCORD_STYLES: &[(&str, char, &str)] = &[
+    ("Short time", 't', "%H:%M"),
+    ("Long time", 'T', "%H:%M:%S"),
+    ("Short date", 'd', "%d/%m/%Y"),
+    ("Long date", 'D', "%-d %B %Y"),
+    ("Short date/time", 'f', "%-d %B %Y %H:%M"),
+    ("Long date/time", 'F', "%A, %-d %B %Y %H:%M"),
+    ("Relative", 'R', ""),
+];
+
+/// Slack date tokens, with a strftime pattern approximating their rendering.
+/// `{ago}` is rendered relative to now instead.
+const SLACK_TOKENS: &[(&str, &str)] = &[
+    ("{date_num}", "%Y-%m-%d"),
+    ("{date_short}", "%b %-d, %Y"),
+    ("{date_long}", "%A, %B %-d, %Y"),
+    ("{date_short} {time}", "%b %-d, %Y %-I:%M %p"),
+    ("{date_short_pretty}", "%b %-d, %Y"),
+    ("{ago}", ""),
+];
+
+/// Discord and Slack markup showing `time` in each reader's own time zone.
+pub fn snippet_matches<Tz: TimeZone>(
+    time: &DateTime<Tz>,
+    now: &DateTime<Zone>,
+) -> Vec<SimpleMatch> {
+    let local = time.with_timezone(&now.timezone());
+    let unix = time.timestamp();
+    let mut groups = Groups::new()
+        .with_group(Group::new(DISCORD, "internet-chat"))
+        .with_group(Group::new(SLACK, "internet-chat"));
+
+    for (name, style, pattern) in DISCORD_STYLES {
+        let preview = match style {
+            'R' => relative(&local, now),
+            _ => local.format(pattern).to_string(),
+        };
+        let snippet = format!("<t:{}:{}>", unix, style);
+        groups.push(DISCORD, snippet_match(name, &preview, snippet));
+    }
+
+    // Clients which can't render the date show the fallback text instead
+    let fallback = time.to_utc().format("%Y-%m-%d %H:%M UTC");
+    for (token, pattern) in SLACK_TOKENS {
+        let preview = match *token {
+            "{ago}" => relative(&local, now),
+            _ => local.format(pattern).to_string(),
+        };
+        let snippet = format!("<!date^{}^{}|{}>", unix, token, fallback);
+        groups.push(SLACK, snippet_match(token, &preview, snippet));
+    }
+
+    groups.into_matches()
+}
+
+fn snippet_match(name: &str, preview: &str, snippet: String) -> SimpleMatch {
+    let title = format!("{}: {}", name, preview);
+    let copy = Action::Copy(snippet);
+    SimpleMatch::new(&title, "", &copy.to_string())
+}
+
+/// Rough distance from `now`, like `in 2 hours` or `3 days ago`.
+fn relative(time: &DateTime<Zone>, now: &DateTime<Zone>) -> String {
+    let seconds = (*time - *now).num_seconds();
+    let minutes = seconds.abs() / 60;
+    let (count, unit) = match minutes {
+        0 => return "now".to_string(),
+        1..60 => (minutes, "minute"),
+        60..1_440 => (minutes / 60, "hour"),
+        1_440..43_200 => (minutes / 1_440, "day"),
+        43_200..525_600 => (minutes / 43_200, "month"),
+        _ => (minutes / 525_600, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    if seconds > 0 {
+        format!("in {} {}{}", count, unit, plural)
+    } else {
+        format!("{} {}{} ago", count, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use matcher::clock::{Clock, FakeClock};
+
+    use super::*;
+
+    #[test]
+    fn test_relative() {
+        let now = FakeClock::at("2025-11-06 17:00:00").now();
+        let relative = |duration| relative(&(now + duration), &now);
+
+        assert_eq!(relative(Duration::seconds(30)), "now");
+        assert_eq!(relative(Duration::minutes(1)), "in 1 minute");
+        assert_eq!(relative(Duration::hours(-2)), "2 hours ago");
+        assert_eq!(relative(Duration::days(3)), "in 3 days");
+        assert_eq!(relative(Duration::days(-65)), "2 months ago");
+        assert_eq!(relative(Duration::days(800)), "in 2 years");
+    }
+}
@@ -0,0 +1,284 @@
+//! Timers and alarms that notify after anyrun has closed.
+//!
+//! Each timer is a transient systemd user timer, which runs the notifier when
+//! it elapses. The timers scheduled from anyrun are also kept in a state file
+//! so that `:ts timers` can list and cancel them.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyrun_plugin::HandleResult;
+use chrono::{DateTime, Days};
+use matcher::{
+    SimpleMatch,
+    clock::{Clock, Zone},
+    context::Context,
+    date_expression::{parse_duration, parse_time_of_day},
+    files::FileWriter,
+    runner::{CommandRunner, quote},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::id::generate_ulid;
+use crate::parse::parse_timestamp;
+
+const SCHEDULE: &str = "timer|";
+const CANCEL: &str = "cancel-timer|";
+/// Prefix of the systemd units, followed by the timer's ID.
+const UNIT_PREFIX: &str = "anyrun-timer-";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timer {
+    pub id: String,
+    pub label: String,
+    /// Unix timestamp at which the timer elapses.
+    pub due: i64,
+}
+
+impl Timer {
+    fn due(&self, zone: &Zone) -> Option<DateTime<Zone>> {
+        Some(DateTime::from_timestamp(self.due, 0)?.with_timezone(zone))
+    }
+}
+
+pub struct Timers {
+    path: PathBuf,
+    runner: Arc<dyn CommandRunner>,
+    clock: Arc<dyn Clock>,
+    files: FileWriter,
+    /// Command run with the label and a body as its last two arguments.
+    notifier: String,
+}
+
+impl Timers {
+    /// Timers stored in the state directory of the plugin.
+    pub fn for_plugin(context: &Context, notifier: &str) -> Option<Self> {
+        Some(Timers {
+            path: context.state_dir("timestamp")?.join("timers.jsonl"),
+            runner: context.runner.clone(),
+            clock: context.clock.clone(),
+            files: context.files,
+            notifier: notifier.to_string(),
+        })
+    }
+
+    /// Reads `timer <duration> [label]` or `at <time> [label]`. A time of day
+    /// that has passed refers to tomorrow.
+    pub fn parse(&self, input: &[&str]) -> Option<(String, DateTime<Zone>)> {
+        let now = self.clock.now();
+        let (label, due) = match input {
+            ["timer", duration, label @ ..] => {
+                (label, now.checked_add_signed(parse_duration(duration)?)?)
+            }
+            ["at", rest @ ..] => (1..=rest.len()).rev().find_map(|split| {
+                let text = rest[..split].join(" ");
+                let mut due = parse_timestamp(&*self.clock, &text)?.with_timezone(&now.timezone());
+                if due <= now && parse_time_of_day(&text).is_some() {
+                    due = due.checked_add_days(Days::new(1))?;
+                }
+                Some((&rest[split..], due))
+            })?,
+            _ => return None,
+        };
+        if due <= now {
+            return None;
+        }
+        let label = match label {
+            [] if input[0] == "at" => "Alarm".to_string(),
+            [] => "Timer".to_string(),
+            label => label.join(" "),
+        };
+        Some((label, due))
+    }
+
+    /// Match that schedules a timer when selected.
+    pub fn schedule_match(&self, label: &str, due: &DateTime<Zone>) -> SimpleMatch {
+        let title = format!("{} at {}", label, self.format_due(due));
+        let description = format!("{}{}|{}", SCHEDULE, due.timestamp(), label);
+        SimpleMatch::new(&title, "alarm-symbolic", &description)
+    }
+
+    /// Matches that cancel the active timers, the earliest first.
+    pub fn timer_matches(&self) -> Vec<SimpleMatch> {
+        let timers = self.active();
+        if timers.is_empty() {
+            return vec![SimpleMatch::new("No active timers", "alarm-symbolic", "")];
+        }
+        timers
+            .into_iter()
+            .filter_map(|timer| {
+                let title = format!(
+                    "Cancel {} at {}",
+                    timer.label,
+                    self.format_due(&timer.due(&self.clock.zone())?)
+                );
+                let description = format!("{}{}", CANCEL, timer.id);
+                Some(SimpleMatch::new(&title, "alarm-symbolic", &description))
+            })
+            .collect()
+    }
+
+    /// Schedules or cancels the timer of a selected match, or returns `None`
+    /// if the match isn't about timers.
+    pub fn handle(&self, description: &str) -> Option<HandleResult> {
+        let result = if let Some(timer) = description.strip_prefix(SCHEDULE) {
+            let (due, label) = timer.split_once('|')?;
+            let due =
+                DateTime::from_timestamp(due.parse().ok()?, 0)?.with_timezone(&self.clock.zone());
+            self.schedule(label, &due).map(|_| HandleResult::Close)
+        } else if let Some(id) = description.strip_prefix(CANCEL) {
+            // Stay open so the remaining timers are listed
+            self.cancel(id).map(|_| HandleResult::Refresh(false))
+        } else {
+            return None;
+        };
+
+        // TODO: Show errors to the user
+        Some(result.unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            HandleResult::Refresh(false)
+        }))
+    }
+
+    /// Timers that haven't elapsed yet, the earliest first.
+    pub fn active(&self) -> Vec<Timer> {
+        let now = self.clock.now().timestamp();
+        let mut timers = fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Timer>(line).ok())
+            .filter(|timer| timer.due > now)
+            .collect::<Vec<_>>();
+        timers.sort_by_key(|timer| timer.due);
+        timers
+    }
+
+    pub fn schedule(&self, label: &str, due: &DateTime<Zone>) -> Result<Timer, String> {
+        let timer = Timer {
+            id: generate_ulid(&self.clock.now()).to_lowercase(),
+            label: label.to_string(),
+            due: due.timestamp(),
+        };
+        let body = format!("Timer set for {}", due.format("%H:%M"));
+        let notify = format!("{} {} {}", self.notifier, quote(label), quote(&body));
+        // systemd reads times without a zone in its own, which may not be the
+        // clock's, so the time is given in UTC
+        self.run(&format!(
+            "systemd-run --user --collect --unit={}{} --on-calendar={} --timer-property=AccuracySec=1s sh -c {}",
+            UNIT_PREFIX,
+            timer.id,
+            quote(&due.to_utc().format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+            quote(&notify),
+        ))?;
+
+        let mut timers = self.active();
+        timers.push(timer.clone());
+        self.write_timers(&timers)?;
+        Ok(timer)
+    }
+
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        self.run(&format!(
+            "systemctl --user stop {}{}.timer",
+            UNIT_PREFIX, id
+        ))?;
+        let mut timers = self.active();
+        timers.retain(|timer| timer.id != id);
+        self.write_timers(&timers)
+    }
+
+    fn run(&self, command: &str) -> Result<(), String> {
+        let output = self
+            .runner
+            .execute(command)
+            .map_err(|e| format!("Error running '{}': {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!("'{}' failed with {}", command, output.status));
+        }
+        Ok(())
+    }
+
+    /// Time of day, with the date if it isn't today.
+    fn format_due(&self, due: &DateTime<Zone>) -> String {
+        if due.date_naive() == self.clock.now().date_naive() {
+            due.format("%H:%M").to_string()
+        } else {
+            due.format("%a %-d %b %H:%M").to_string()
+        }
+    }
+
+    fn write_timers(&self, timers: &[Timer]) -> Result<(), String> {
+        let mut content = String::new();
+        for timer in timers {
+            let line = serde_json::to_string(timer)
+                .map_err(|e| format!("Failed to serialize timer: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        self.files.write(&self.path, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use matcher::testing::Harness;
+
+    use super::*;
+
+    #[test]
+    fn test_schedule_command() {
+        let harness = Harness::new()
+            .with_zone(chrono_tz::Europe::Berlin)
+            .with_time("2025-11-06 17:04:05")
+            .with_command("systemd-run", "");
+        let timers = Timers::for_plugin(&harness.context(), "stub-notify").unwrap();
+
+        let (label, due) = timers.parse(&["timer", "10m", "tea"]).unwrap();
+        let timer = timers.schedule(&label, &due).unwrap();
+        assert_eq!(
+            harness.calls(),
+            [format!(
+                "systemd-run --user --collect --unit=anyrun-timer-{} \
+                 --on-calendar='2025-11-06 16:14:05 UTC' --timer-property=AccuracySec=1s \
+                 sh -c 'stub-notify '\\''tea'\\'' '\\''Timer set for 17:14'\\'''",
+                timer.id
+            )]
+        );
+    }
+
+    #[test]
+    fn test_failed_schedule() {
+        let harness = Harness::new()
+            .with_time("2025-11-06 17:04:05")
+            .with_command_output("systemd-run", 1, "");
+        let timers = Timers::for_plugin(&harness.context(), "stub-notify").unwrap();
+
+        let (label, due) = timers.parse(&["timer", "10m"]).unwrap();
+        assert_eq!(label, "Timer");
+        assert!(timers.schedule(&label, &due).is_err());
+        assert_eq!(timers.active(), []);
+        assert!(timers.handle("copy|tea").is_none());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let harness = Harness::new()
+            .with_time("2025-11-06 17:04:05")
+            .with_dry_run();
+        let timers = Timers::for_plugin(&harness.context(), "stub-notify").unwrap();
+
+        let (label, due) = timers.parse(&["timer", "10m", "tea"]).unwrap();
+        timers.schedule(&label, &due).unwrap();
+        assert!(harness.calls().is_empty());
+        assert_eq!(timers.active(), []);
+    }
+
+    #[test]
+    fn test_parse_overflow() {
+        let harness = Harness::new().with_time("2025-11-06 17:04:05");
+        let timers = Timers::for_plugin(&harness.context(), "stub-notify").unwrap();
+
+        assert_eq!(timers.parse(&["timer", "99999999d"]), None);
+        assert_eq!(timers.parse(&["timer", "99999999w", "tea"]), None);
+    }
+}
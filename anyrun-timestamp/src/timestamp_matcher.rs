@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use anyrun_plugin::HandleResult;
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
+use matcher::{
+    Matcher, SimpleMatch, action::Action, clock::Clock, group::Groups,
+    matcher_static::StaticMatcher,
+};
+
+use crate::diff::{diff_matches, parse_diff};
+use crate::facts::fact_matches;
+use crate::format::TimestampFormat;
+use crate::id::decode_id;
+use crate::parse::{parse_timestamp, parse_timestamp_in};
+use crate::snippets::snippet_matches;
+use crate::timer::Timers;
+use crate::zone::find_zone;
+
+/// Number of trailing words tried as the zone of the time to convert, enough
+/// for names like `new york`.
+const MAX_ZONE_WORDS: usize = 3;
+
+/// Converts a pasted timestamp into every format, or offers the formats for
+/// the current time when the input isn't a timestamp.
+pub struct TimestampMatcher {
+    pub root: StaticMatcher,
+    pub formats: Vec<TimestampFormat>,
+    pub clock: Arc<dyn Clock>,
+    /// Timers scheduled by `:ts timer` and `:ts at`, if there is a state
+    /// directory to keep them in.
+    pub timers: Option<Timers>,
+}
+
+impl Matcher for TimestampMatcher {
+    fn get_matches(&self, input: Vec<&str>) -> Vec<SimpleMatch> {
+        if let Some(timers) = &self.timers {
+            if input[..] == ["timers"] {
+                return timers.timer_matches();
+            }
+            if let Some((label, due)) = timers.parse(&input) {
+                return vec![timers.schedule_match(&label, &due)];
+            }
+        }
+
+        if let ["snippet" | "snippets", date @ ..] = &input[..] {
+            let now = self.clock.now();
+            let time = match date {
+                [] => Some(now.fixed_offset()),
+                date => parse_timestamp(&*self.clock, &date.join(" ")),
+            };
+            return time
+                .map(|time| snippet_matches(&time, &now))
+                .unwrap_or_default();
+        }
+
+        if let ["diff", dates @ ..] = &input[..] {
+            return match parse_diff(&*self.clock, dates) {
+                Some((from, to)) => diff_matches(&from, &to),
+                None => Vec::new(),
+            };
+        }
+
+        if let Some((time, zone)) = self.parse_conversion(&input) {
+            let time = time.with_timezone(&zone);
+            let short = time.format("%H:%M %Z").to_string();
+            let title = format!("{}, {}", short, time.format("%a %-d %b"));
+            let copy = Action::Copy(short);
+            let mut matches = vec![SimpleMatch::new(
+                &title,
+                "preferences-system-time",
+                &copy.to_string(),
+            )];
+            for format in &self.formats {
+                matches.push(format_match(format, zone.name(), format.format(&time)));
+            }
+            return matches;
+        }
+
+        // A number can be an epoch and a snowflake, each read is grouped then
+        let text = input.join(" ");
+        let mut times = Vec::new();
+        if let Some(time) = parse_timestamp(&*self.clock, &text) {
+            times.push(("Timestamp", time.to_utc()));
+        }
+        times.extend(decode_id(&text, self.clock.now().to_utc()));
+        match &times[..] {
+            [] => self.root.get_matches(input),
+            [(_, time)] => self.time_matches(time),
+            _ => {
+                let mut groups = Groups::new();
+                for (kind, time) in &times {
+                    for time_match in self.time_matches(time) {
+                        groups.push(kind, time_match);
+                    }
+                }
+                groups.into_matches()
+            }
+        }
+    }
+
+    fn handle(&self, selection: SimpleMatch) -> HandleResult {
+        if let Some(timers) = &self.timers
+            && let Some(result) = timers.handle(&selection.description)
+        {
+            return result;
+        }
+        self.root.handle(selection)
+    }
+}
+
+impl TimestampMatcher {
+    /// Every format of `time` in the clock's zone and UTC, then its calendar
+    /// facts.
+    fn time_matches(&self, time: &DateTime<Utc>) -> Vec<SimpleMatch> {
+        let local = time.with_timezone(&self.clock.zone());
+        let mut matches = Vec::new();
+        for format in &self.formats {
+            matches.push(format_match(format, "local", format.format(&local)));
+            matches.push(format_match(format, "UTC", format.format(time)));
+        }
+        matches.extend(fact_matches(local.date_naive()));
+        matches
+    }
+
+    /// Reads `<time> [zone] in|to <zone>`. The time is read in the clock's
+    /// zone unless it is followed by a zone.
+    fn parse_conversion(&self, input: &[&str]) -> Option<(DateTime<FixedOffset>, Tz)> {
+        let split = input
+            .iter()
+            .rposition(|word| *word == "in" || *word == "to")?;
+        let (source, target) = (&input[..split], &input[split + 1..]);
+        if source.is_empty() {
+            return None;
+        }
+        let target = find_zone(&target.join(" "))?;
+
+        for words in 1..=MAX_ZONE_WORDS.min(source.len() - 1) {
+            let (time, zone) = source.split_at(source.len() - words);
+            if let Some(zone) = find_zone(&zone.join(" "))
+                && let Some(time) = parse_timestamp_in(&*self.clock, &time.join(" "), &zone)
+            {
+                return Some((time, target));
+            }
+        }
+        let time = parse_timestamp(&*self.clock, &source.join(" "))?;
+        Some((time, target))
+    }
+}
+
+fn format_match(format: &TimestampFormat, zone: &str, value: String) -> SimpleMatch {
+    let title = format!("{} ({})", format.name, zone);
+    let copy = Action::Copy(value);
+    SimpleMatch::new(&title, "x-office-calendar", &copy.to_string())
+}
@@ -0,0 +1,98 @@
+use chrono_tz::{TZ_VARIANTS, Tz};
+use serde::{Deserialize, Deserializer};
+
+/// Common abbreviations, mapped to a zone observing them so that daylight
+/// saving time is applied on the right dates.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("utc", "UTC"),
+    ("gmt", "UTC"),
+    ("z", "UTC"),
+    ("pt", "America/Los_Angeles"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("mt", "America/Denver"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("ct", "America/Chicago"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("et", "America/New_York"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("bst", "Europe/London"),
+    ("wet", "Europe/Lisbon"),
+    ("west", "Europe/Lisbon"),
+    ("cet", "Europe/Berlin"),
+    ("cest", "Europe/Berlin"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("msk", "Europe/Moscow"),
+    ("ist", "Asia/Kolkata"),
+    ("sgt", "Asia/Singapore"),
+    ("hkt", "Asia/Hong_Kong"),
+    ("jst", "Asia/Tokyo"),
+    ("kst", "Asia/Seoul"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+    ("nzst", "Pacific/Auckland"),
+    ("nzdt", "Pacific/Auckland"),
+];
+
+/// Looks up a zone by abbreviation, IANA name or city, ignoring case. Spaces
+/// may be used instead of underscores, as in `new york`.
+pub fn find_zone(name: &str) -> Option<Tz> {
+    let name = name.trim().to_lowercase().replace(' ', "_");
+    if name.is_empty() {
+        return None;
+    }
+
+    if let Some((_, zone)) = ABBREVIATIONS.iter().find(|(abbr, _)| *abbr == name) {
+        return zone.parse().ok();
+    }
+    TZ_VARIANTS
+        .iter()
+        .find(|zone| zone.name().to_lowercase() == name)
+        .or_else(|| {
+            TZ_VARIANTS.iter().find(|zone| {
+                zone.name()
+                    .rsplit_once('/')
+                    .is_some_and(|(_, city)| city.to_lowercase() == name)
+            })
+        })
+        .copied()
+}
+
+/// Deserializes a list of zone names, skipping the unknown ones.
+pub fn deserialize_zones<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Tz>, D::Error> {
+    let zones = Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(|name| {
+            let zone = find_zone(&name);
+            if zone.is_none() {
+                eprintln!("Skipping unknown time zone '{}'", name);
+            }
+            zone
+        })
+        .collect();
+    Ok(zones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_zone() {
+        let name = |query| find_zone(query).map(|zone| zone.name());
+
+        assert_eq!(name("america/new_york"), Some("America/New_York"));
+        assert_eq!(name("Europe/Paris"), Some("Europe/Paris"));
+        assert_eq!(name("tokyo"), Some("Asia/Tokyo"));
+        assert_eq!(name("new york"), Some("America/New_York"));
+        assert_eq!(name("PST"), Some("America/Los_Angeles"));
+        assert_eq!(name("cet"), Some("Europe/Berlin"));
+        assert_eq!(name("utc"), Some("UTC"));
+        assert_eq!(name("atlantis"), None);
+        assert_eq!(name(""), None);
+    }
+}
@@ -1,18 +1,63 @@
 mod todo;
-use std::fs::{OpenOptions, read_to_string};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use abi_stable::std_types::{RString, RVec};
 use anyrun_plugin::{HandleResult, Match, PluginInfo, get_matches, handler, info, init};
 use matcher::{
-    Matcher, NoopMatcher, SimpleMatch, handler_wildcard_command::WildcardHandler,
+    Matcher, NoopMatcher, SimpleMatch,
+    action::Action,
+    config::load_config,
+    context::Context,
+    handler_undo::UndoHandler,
+    handler_wildcard_command::WildcardHandler,
+    history::{History, LastQuery},
+    journal::Journal,
     matcher_static::StaticMatcher,
+    notifier::{CommandNotifier, DEFAULT_NOTIFIER},
 };
+use serde::Deserialize;
 
 const PREFIX: &str = ":todo";
 const TODO_FILE: &str = "/home/rmacwha/Documents/todos.txt";
 
+/// Configuration read from `todo.ron` in the anyrun config directory.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    /// Print the selected value to stdout instead of copying or executing it.
+    stdout: bool,
+    /// Send a desktop notification after running a command.
+    notify: bool,
+    /// Notifier command, run with the summary and body as arguments.
+    notifier: Option<String>,
+    /// Also copy the title of a todo when completing it.
+    copy_completed: bool,
+    /// Path of the todo file, `TODO_FILE` by default.
+    file: Option<PathBuf>,
+}
+
+impl Config {
+    fn todo_file(&self) -> &Path {
+        self.file.as_deref().unwrap_or(Path::new(TODO_FILE))
+    }
+}
+
+/// Plugin state: the configuration and the last query, recorded in the
+/// history when a match is selected.
+struct State {
+    config: Config,
+    query: LastQuery,
+}
+
 #[init]
-fn init(_config_dir: RString) {}
+fn init(config_dir: RString) -> State {
+    State {
+        config: load_config(&config_dir, "todo.ron"),
+        query: LastQuery::default(),
+    }
+}
 
 #[info]
 fn info() -> PluginInfo {
@@ -23,7 +68,7 @@ fn info() -> PluginInfo {
 }
 
 #[get_matches]
-fn get_matches(input: RString) -> RVec<Match> {
+fn get_matches(input: RString, state: &State) -> RVec<Match> {
     let input = input.trim().to_lowercase();
 
     if !input.starts_with(PREFIX) {
@@ -34,42 +79,59 @@ fn get_matches(input: RString) -> RVec<Match> {
         .trim_start_matches(PREFIX)
         .split_ascii_whitespace()
         .collect::<Vec<_>>();
+    let query = input.join(" ");
+    state.query.set(&query);
 
-    let matcher = get_matcher();
+    let matcher = get_matcher(
+        &Context::system(),
+        &state.config,
+        &query,
+        state.config.todo_file(),
+    );
     let matches = matcher.get_matches(input);
     let matches: Vec<Match> = matches.into_iter().map(|m| m.into()).collect();
     RVec::from(matches)
 }
 
 #[handler]
-fn handler(selection: Match) -> HandleResult {
-    let matcher = get_matcher();
+fn handler(selection: Match, state: &State) -> HandleResult {
+    let matcher = get_matcher(
+        &Context::system(),
+        &state.config,
+        &state.query.get(),
+        state.config.todo_file(),
+    );
     matcher.handle(selection.into())
 }
 
-fn ensure_todo_file_exists() {
-    if let Some(parent) = std::path::Path::new(TODO_FILE).parent() {
-        let _ = std::fs::create_dir_all(parent);
+fn ensure_todo_file_exists(context: &Context, todo_file: &Path) {
+    if !todo_file.exists() {
+        let _ = context.files.write(todo_file, "");
     }
-    let _ = OpenOptions::new().create(true).append(true).open(TODO_FILE);
 }
 
-fn get_matcher() -> Box<dyn Matcher> {
-    ensure_todo_file_exists();
+fn get_matcher(
+    context: &Context,
+    config: &Config,
+    query: &str,
+    todo_file: &Path,
+) -> Box<dyn Matcher> {
+    ensure_todo_file_exists(context, todo_file);
 
-    let todos = read_to_string(TODO_FILE).unwrap_or_default();
+    let todos = read_to_string(todo_file).unwrap_or_default();
     let todos = todos
         .lines()
         .filter_map(|line| todo::Todo::from_str(line))
         .collect::<Vec<todo::Todo>>();
 
-    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let now = context.clock.now();
+    let date = now.format("%Y-%m-%d").to_string();
 
     let mut list_matches = StaticMatcher::new();
     for todo in todos.iter().rev().filter(|t| t.completed_at.is_none()) {
         let incomplete_line = todo.to_string();
         let mut completed_todo = todo.clone();
-        completed_todo.completed_at = Some(chrono::Local::now().date_naive());
+        completed_todo.completed_at = Some(now.date_naive());
         let completed_line = completed_todo.to_string();
 
         // Escape special sed characters
@@ -82,34 +144,268 @@ fn get_matcher() -> Box<dyn Matcher> {
             .replace("[", "\\[")
             .replace("]", "\\]");
 
+        let mut action = Action::Exec(format!(
+            "sed -i 's|{}|{}|' {}",
+            escaped_incomplete,
+            escaped_complete,
+            todo_file.display()
+        ));
+        if config.stdout {
+            action = Action::Stdout(todo.title.clone());
+        } else if config.copy_completed {
+            action = Action::sequence(vec![action, Action::Copy(todo.title.clone())], true);
+        }
+
         list_matches = list_matches.with_child(
-            SimpleMatch::new(
-                &todo.title,
-                "text-x-generic",
-                &format!(
-                    "exec|sed -i 's|{}|{}|' {}",
-                    escaped_incomplete, escaped_complete, TODO_FILE
-                ),
-            ),
+            SimpleMatch::new(&todo.title, "text-x-generic", &action.to_string()),
             Box::new(NoopMatcher),
         );
     }
-    let matcher = StaticMatcher::new()
+    // In stdout mode, print the title of the todo instead of adding it
+    let add = if config.stdout {
+        Action::Stdout("{}".into())
+    } else {
+        Action::Exec(format!(
+            "echo '- [ ] {{}} (created at: {}, completed at: None)' >> {}",
+            date,
+            todo_file.display()
+        ))
+    };
+    let mut matcher = StaticMatcher::new()
+        .with_runner(context.runner.clone())
+        .with_stdout(config.stdout)
         .with_child(
             SimpleMatch::new("ls", "text-x-generic", ""),
             Box::new(list_matches),
         )
         .with_child(
             SimpleMatch::new("add", "list-add", ""),
-            Box::new(WildcardHandler::new(
-                "add {}",
-                "",
-                &format!(
-                    "exec|echo '- [ ] {{}} (created at: {}, completed at: None)' >> {}",
-                    date, TODO_FILE
-                ),
-            )),
+            Box::new(WildcardHandler::new("add {}", "", &add.to_string())),
         );
 
+    if config.notify {
+        let command = config.notifier.as_deref().unwrap_or(DEFAULT_NOTIFIER);
+        let notifier = CommandNotifier::new(context.runner.clone(), command);
+        matcher = matcher.with_notifier(Arc::new(notifier));
+    }
+
+    if let Some(history) = History::for_plugin(context, "todo") {
+        matcher = matcher.with_history(history.with_query(query));
+    }
+
+    // Nothing is changed in stdout mode, so there is nothing to undo either
+    if !config.stdout
+        && let Some(journal) = Journal::for_plugin(context, "todo")
+    {
+        let journal = journal.tracking(todo_file);
+        matcher = matcher
+            .with_child(
+                SimpleMatch::new("undo", "edit-undo", "undo|1"),
+                Box::new(UndoHandler::new(journal.clone())),
+            )
+            .with_journal(journal);
+    }
+
     return Box::new(matcher);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use matcher::files::FileWriter;
+    use matcher::testing::{
+        Harness, assert_closed, assert_copied, assert_refreshed, assert_stdout, assert_titles,
+        select,
+    };
+
+    use super::*;
+
+    const TODOS: &str = "- [ ] buy milk (created at: 2025-11-01, completed at: None)\n\
+                         - [x] call bob (created at: 2025-11-02, completed at: 2025-11-03)\n\
+                         - [ ] fix bike (created at: 2025-11-04, completed at: None)\n";
+
+    /// Todo file inside the home directory of the harness.
+    const TODOS_TXT: &str = "Documents/todos.txt";
+
+    fn todo_file(harness: &Harness) -> PathBuf {
+        harness.root().home().join(TODOS_TXT)
+    }
+
+    fn harness() -> Harness {
+        Harness::new()
+            .with_time("2025-11-06 17:00:00")
+            .with_passthrough("echo")
+            .with_passthrough("sed")
+    }
+
+    #[test]
+    fn test_list_open_todos() {
+        let harness = harness();
+        harness.write_home(TODOS_TXT, TODOS);
+
+        let matcher = get_matcher(
+            &harness.context(),
+            &Config::default(),
+            "",
+            &todo_file(&harness),
+        );
+        assert_titles(&*matcher, "ls", &["fix bike", "buy milk"]);
+        assert_titles(&*matcher, "ls milk", &["buy milk"]);
+        // Only the watson project matcher tolerates typos
+        assert_titles(&*matcher, "ls mlik", &["ls"]);
+    }
+
+    #[test]
+    fn test_add_todo() {
+        let harness = harness();
+
+        let result = select(
+            &*get_matcher(
+                &harness.context(),
+                &Config::default(),
+                "",
+                &todo_file(&harness),
+            ),
+            "add Water plants",
+            "add water plants",
+        );
+        assert_closed(&result);
+        assert_eq!(
+            harness.read_home(TODOS_TXT),
+            "- [ ] water plants (created at: 2025-11-06, completed at: None)\n"
+        );
+    }
+
+    #[test]
+    fn test_complete_and_copy() {
+        let harness = harness();
+        harness.write_home(TODOS_TXT, TODOS);
+
+        let config = Config {
+            copy_completed: true,
+            ..Config::default()
+        };
+        assert_copied(
+            &select(
+                &*get_matcher(&harness.context(), &config, "", &todo_file(&harness)),
+                "ls bike",
+                "fix bike",
+            ),
+            "fix bike",
+        );
+        assert!(harness.read_home(TODOS_TXT).contains("- [x] fix bike"));
+    }
+
+    #[test]
+    fn test_complete_and_undo() {
+        let harness = harness();
+        harness.write_home(TODOS_TXT, TODOS);
+
+        assert_closed(&select(
+            &*get_matcher(
+                &harness.context(),
+                &Config::default(),
+                "",
+                &todo_file(&harness),
+            ),
+            "ls milk",
+            "buy milk",
+        ));
+        assert_eq!(
+            harness.read_home(TODOS_TXT).lines().next(),
+            Some("- [x] buy milk (created at: 2025-11-01, completed at: 2025-11-06)")
+        );
+
+        let matcher = get_matcher(
+            &harness.context(),
+            &Config::default(),
+            "",
+            &todo_file(&harness),
+        );
+        assert_titles(&*matcher, "ls", &["fix bike"]);
+        assert_closed(&select(
+            &*matcher,
+            "undo",
+            "undo 1: '- [ ] buy milk (created at: 2025-11-01, completed at: None)' -> \
+             '- [x] buy milk (created at: 2025-11-01, completed at: 2025-11-06)'",
+        ));
+        assert_eq!(harness.read_home(TODOS_TXT), TODOS);
+        assert_titles(
+            &*get_matcher(
+                &harness.context(),
+                &Config::default(),
+                "",
+                &todo_file(&harness),
+            ),
+            "undo",
+            &["undo"],
+        );
+    }
+
+    #[test]
+    fn test_stdout() {
+        let harness = harness();
+        harness.write_home(TODOS_TXT, TODOS);
+        let config = Config {
+            stdout: true,
+            copy_completed: true,
+            ..Config::default()
+        };
+        let matcher = get_matcher(&harness.context(), &config, "", &todo_file(&harness));
+
+        assert_stdout(&select(&*matcher, "ls bike", "fix bike"), "fix bike");
+        assert_stdout(
+            &select(&*matcher, "add water plants", "add water plants"),
+            "water plants",
+        );
+        assert_titles(&*matcher, "undo", &[]);
+        assert!(harness.calls().is_empty());
+        assert_eq!(harness.read_home(TODOS_TXT), TODOS);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let harness = harness().with_dry_run();
+        let context = harness.context();
+        let path = todo_file(&harness);
+        assert_titles(
+            &*get_matcher(&context, &Config::default(), "", &path),
+            "ls",
+            &["ls"],
+        );
+        assert!(!path.exists());
+
+        harness.write_home(TODOS_TXT, TODOS);
+        assert_closed(&select(
+            &*get_matcher(&context, &Config::default(), "", &path),
+            "ls milk",
+            "buy milk",
+        ));
+        assert!(harness.calls().is_empty());
+        assert_eq!(harness.read_home(TODOS_TXT), TODOS);
+        assert!(!context.state_dir("todo").unwrap().exists());
+
+        // Changes recorded earlier are neither undone nor forgotten
+        let files = FileWriter::default();
+        let journal = Journal::for_plugin(&context, "todo").unwrap();
+        let completed = TODOS.replacen("- [ ] buy", "- [x] buy", 1);
+        harness.write_home(TODOS_TXT, &completed);
+        journal
+            .clone()
+            .with_files(files)
+            .record(&path, TODOS, &completed)
+            .unwrap();
+        let history = History::for_plugin(&context, "todo").unwrap();
+        let add = SimpleMatch::new("add tea", "", "exec|echo tea");
+        history.clone().with_files(files).record(&add).unwrap();
+
+        let matcher = get_matcher(&context, &Config::default(), "", &path);
+        let undo = matcher.get_matches(vec!["undo"]).remove(0);
+        assert_closed(&matcher.handle(undo));
+        assert_refreshed(&select(&*matcher, "", "clear history"));
+        assert_eq!(harness.read_home(TODOS_TXT), completed);
+        assert_eq!(journal.entries().len(), 1);
+        assert_eq!(history.entries().len(), 1);
+    }
+}
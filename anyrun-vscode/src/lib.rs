@@ -1,6 +1,13 @@
 use abi_stable::std_types::{RString, RVec};
 use anyrun_plugin::{HandleResult, Match, PluginInfo, get_matches, handler, info, init};
-use matcher::{Matcher, NoopMatcher, SimpleMatch, matcher_static::StaticMatcher};
+use matcher::{
+    Matcher, NoopMatcher, SimpleMatch,
+    action::Action,
+    config::load_config,
+    context::Context,
+    history::{History, LastQuery},
+    matcher_static::StaticMatcher,
+};
 use rusqlite::Connection;
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -21,8 +28,28 @@ struct Entry {
 const PREFIX: &str = ":vs";
 const MAX_RESULTS: usize = 10;
 
+/// Configuration read from `vscode.ron` in the anyrun config directory.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    /// Print the selected value to stdout instead of copying or executing it.
+    stdout: bool,
+}
+
+/// Plugin state: the configuration and the last query, recorded in the
+/// history when a match is selected.
+struct State {
+    config: Config,
+    query: LastQuery,
+}
+
 #[init]
-fn init(_config_dir: RString) {}
+fn init(config_dir: RString) -> State {
+    State {
+        config: load_config(&config_dir, "vscode.ron"),
+        query: LastQuery::default(),
+    }
+}
 
 #[info]
 fn info() -> PluginInfo {
@@ -33,7 +60,7 @@ fn info() -> PluginInfo {
 }
 
 #[get_matches]
-fn get_matches(input: RString) -> RVec<Match> {
+fn get_matches(input: RString, state: &State) -> RVec<Match> {
     let input = input.trim().to_lowercase();
 
     if !input.starts_with(PREFIX) {
@@ -44,8 +71,10 @@ fn get_matches(input: RString) -> RVec<Match> {
         .trim_start_matches(PREFIX)
         .split_ascii_whitespace()
         .collect::<Vec<_>>();
+    let query = input.join(" ");
+    state.query.set(&query);
 
-    let matcher = get_matcher();
+    let matcher = get_matcher(&Context::system(), &state.config, &query);
     let matcher = match matcher {
         Ok(m) => m,
         Err(e) => {
@@ -60,8 +89,8 @@ fn get_matches(input: RString) -> RVec<Match> {
 }
 
 #[handler]
-fn handler(selection: Match) -> HandleResult {
-    let matcher = get_matcher();
+fn handler(selection: Match, state: &State) -> HandleResult {
+    let matcher = get_matcher(&Context::system(), &state.config, &state.query.get());
     let matcher = match matcher {
         Ok(m) => m,
         Err(_) => {
@@ -71,31 +100,47 @@ fn handler(selection: Match) -> HandleResult {
     matcher.handle(selection.into())
 }
 
-fn get_matcher() -> Result<Box<dyn Matcher>, String> {
-    let recent_projects = match get_recent_projects() {
+fn get_matcher(
+    context: &Context,
+    config: &Config,
+    query: &str,
+) -> Result<Box<dyn Matcher>, String> {
+    let recent_projects = match get_recent_projects(context) {
         Ok(projects) => projects,
         Err(e) => return Err(e),
     };
 
-    let mut matcher = StaticMatcher::new().with_max_results(MAX_RESULTS);
+    let mut matcher = StaticMatcher::new()
+        .with_runner(context.runner.clone())
+        .with_max_results(MAX_RESULTS)
+        .with_acronyms(true);
     for project in recent_projects {
         let name = project
             .rsplit(std::path::MAIN_SEPARATOR)
             .next()
             .unwrap_or(&project)
             .to_string();
-        let command = format!("exec|code \"{}\"", project);
+        // In stdout mode, print the workspace path rather than the command opening it
+        let action = if config.stdout {
+            Action::Stdout(project.clone())
+        } else {
+            Action::Exec(format!("code \"{}\"", project))
+        };
         matcher = matcher.with_child(
-            SimpleMatch::new(&name, "folder", &command),
+            SimpleMatch::new(&name, "folder", &action.to_string()),
             Box::new(NoopMatcher),
         );
     }
 
+    if let Some(history) = History::for_plugin(context, "vscode") {
+        matcher = matcher.with_history(history.with_query(query));
+    }
+
     Ok(Box::new(matcher))
 }
 
-fn get_recent_projects() -> Result<Vec<String>, String> {
-    let path = vscode_state_path().ok_or("Could not locate VSCode state file path")?;
+fn get_recent_projects(context: &Context) -> Result<Vec<String>, String> {
+    let path = vscode_state_path(context).ok_or("Could not locate VSCode state file path")?;
     if !path.exists() {
         return Err("VSCode state file not found".into());
     }
@@ -128,17 +173,17 @@ fn get_recent_projects() -> Result<Vec<String>, String> {
     Ok(result)
 }
 
-fn vscode_state_path() -> Option<PathBuf> {
+fn vscode_state_path(context: &Context) -> Option<PathBuf> {
     #[cfg(target_os = "linux")]
     {
-        let mut path = dirs::home_dir()?;
+        let mut path = context.home_dir.clone()?;
         path.push(".config/Code/User/globalStorage/state.vscdb");
         return Some(path);
     }
 
     #[cfg(target_os = "macos")]
     {
-        let mut path = dirs::home_dir()?;
+        let mut path = context.home_dir.clone()?;
         path.push("Library/Application Support/Code/User/globalStorage/state.vscdb");
         return Some(path);
     }
@@ -154,3 +199,106 @@ fn vscode_state_path() -> Option<PathBuf> {
     #[allow(unreachable_code)]
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use matcher::testing::{Harness, assert_closed, assert_stdout, assert_titles, find, select};
+
+    use super::*;
+
+    fn write_state(harness: &Harness, json: &str) {
+        let path = vscode_state_path(&harness.context()).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        assert!(path.starts_with(harness.root().home()));
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute("CREATE TABLE ItemTable (key TEXT, value TEXT)", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO ItemTable VALUES ('history.recentlyOpenedPathsList', ?1)",
+            [json],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_recent_workspaces() {
+        let harness = Harness::new();
+        write_state(
+            &harness,
+            r#"{"entries": [
+                {"folderUri": "file:///home/user/anyrun-plugins"},
+                {"workspace": "file:///home/user/work/api.code-workspace"},
+                {"fileUri": "file:///home/user/notes.md"}
+            ]}"#,
+        );
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "").unwrap();
+        assert_titles(&*matcher, "", &["anyrun-plugins", "api.code-workspace"]);
+        let project = find(&*matcher, "plugins", "anyrun-plugins");
+        assert_eq!(
+            project.description,
+            "exec|code \"/home/user/anyrun-plugins\""
+        );
+    }
+
+    #[test]
+    fn test_acronyms() {
+        let harness = Harness::new();
+        write_state(
+            &harness,
+            r#"{"entries": [
+                {"folderUri": "file:///home/user/anyrun-plugins-experimental"},
+                {"folderUri": "file:///home/user/notes"}
+            ]}"#,
+        );
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "").unwrap();
+        assert_titles(&*matcher, "api", &["anyrun-plugins-experimental"]);
+        assert_titles(&*matcher, "ape", &["anyrun-plugins-experimental"]);
+        // Only the watson project matcher tolerates typos
+        assert_titles(&*matcher, "expermiental", &[]);
+    }
+
+    #[test]
+    fn test_stdout_prints_path() {
+        let harness = Harness::new();
+        write_state(
+            &harness,
+            r#"{"entries": [{"folderUri": "file:///home/user/anyrun-plugins"}]}"#,
+        );
+
+        let config = Config { stdout: true };
+        let matcher = get_matcher(&harness.context(), &config, "").unwrap();
+        assert_stdout(
+            &select(&*matcher, "plugins", "anyrun-plugins"),
+            "/home/user/anyrun-plugins",
+        );
+        assert!(harness.calls().is_empty());
+    }
+
+    #[test]
+    fn test_history_recall() {
+        let harness = Harness::new().with_command("code", "");
+        write_state(
+            &harness,
+            r#"{"entries": [{"folderUri": "file:///home/user/anyrun-plugins"}]}"#,
+        );
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "plug").unwrap();
+        assert_closed(&select(&*matcher, "plug", "anyrun-plugins"));
+        harness.assert_ran("code \"/home/user/anyrun-plugins\"");
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "").unwrap();
+        assert_titles(&*matcher, "", &["plug", "clear history", "anyrun-plugins"]);
+    }
+
+    #[test]
+    fn test_missing_state_file() {
+        let harness = Harness::new();
+        assert_eq!(
+            get_recent_projects(&harness.context()).unwrap_err(),
+            "VSCode state file not found"
+        );
+    }
+}
@@ -1,20 +1,53 @@
 mod watson;
 mod watson_matcher;
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use abi_stable::std_types::{RString, RVec};
 use anyrun_plugin::{HandleResult, Match, PluginInfo, get_matches, handler, info, init};
 use matcher::{
-    Matcher, NoopMatcher, SimpleMatch, handler_display_command::CommandDisplayHandler,
+    Matcher, NoopMatcher, SimpleMatch,
+    action::Action,
+    config::load_config,
+    context::Context,
+    handler_display_command::CommandDisplayHandler,
+    history::{History, LastQuery},
     matcher_static::StaticMatcher,
+    notifier::{CommandNotifier, DEFAULT_NOTIFIER},
 };
 
+use serde::Deserialize;
+
 use crate::{watson::get_current_status, watson_matcher::ProjectMatcher};
 
 const PREFIX: &str = ":tt";
 
+/// Configuration read from `watson.ron` in the anyrun config directory.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    /// Print the selected value to stdout instead of copying or executing it.
+    stdout: bool,
+    /// Send a desktop notification after running a command.
+    notify: bool,
+    /// Notifier command, run with the summary and body as arguments.
+    notifier: Option<String>,
+}
+
+/// Plugin state: the configuration and the last query, recorded in the
+/// history when a match is selected.
+struct State {
+    config: Config,
+    query: LastQuery,
+}
+
 #[init]
-fn init(_config_dir: RString) {}
+fn init(config_dir: RString) -> State {
+    State {
+        config: load_config(&config_dir, "watson.ron"),
+        query: LastQuery::default(),
+    }
+}
 
 #[info]
 fn info() -> PluginInfo {
@@ -25,7 +58,7 @@ fn info() -> PluginInfo {
 }
 
 #[get_matches]
-fn get_matches(input: RString) -> RVec<Match> {
+fn get_matches(input: RString, state: &State) -> RVec<Match> {
     let input = input.trim().to_lowercase();
 
     if !input.starts_with(PREFIX) {
@@ -36,57 +69,81 @@ fn get_matches(input: RString) -> RVec<Match> {
         .trim_start_matches(PREFIX)
         .split_ascii_whitespace()
         .collect::<Vec<_>>();
+    let query = input.join(" ");
+    state.query.set(&query);
 
-    let matcher = get_matcher();
+    let matcher = get_matcher(&Context::system(), &state.config, &query);
     let matches = matcher.get_matches(input);
     let matches: Vec<Match> = matches.into_iter().map(|m| m.into()).collect();
     RVec::from(matches)
 }
 
 #[handler]
-fn handler(selection: Match) -> HandleResult {
-    let matcher = get_matcher();
+fn handler(selection: Match, state: &State) -> HandleResult {
+    let matcher = get_matcher(&Context::system(), &state.config, &state.query.get());
     matcher.handle(selection.into())
 }
 
-fn get_matcher() -> Box<dyn Matcher> {
-    let mut matcher = StaticMatcher::new();
-
-    let current_status = get_current_status();
-    match current_status {
-        Some((project, _tags, _)) => {
-            matcher = matcher.with_child(
-                SimpleMatch::new(
-                    &format!("stop {}", project),
-                    "media-playback-stop",
-                    "exec|watson stop",
-                ),
-                Box::new(NoopMatcher),
-            );
-        }
-        None => {}
-    };
+fn get_matcher(context: &Context, config: &Config, query: &str) -> Box<dyn Matcher> {
+    let mut matcher = StaticMatcher::new()
+        .with_runner(context.runner.clone())
+        .with_stdout(config.stdout);
+
+    if config.notify {
+        let command = config.notifier.as_deref().unwrap_or(DEFAULT_NOTIFIER);
+        let notifier = CommandNotifier::new(context.runner.clone(), command);
+        matcher = matcher.with_notifier(Arc::new(notifier));
+    }
+
+    if let Some(history) = History::for_plugin(context, "watson") {
+        matcher = matcher.with_history(history.with_query(query));
+    }
+
+    // Only offer to stop while a frame is being tracked
+    let runner = context.runner.clone();
+    let stdout = config.stdout;
+    matcher = matcher.with_lazy_child(
+        "stop",
+        move || {
+            let (project, _tags, _) = get_current_status(runner.as_ref())?;
+            let action = if stdout {
+                Action::Stdout(project.clone())
+            } else {
+                Action::Exec("watson stop".into())
+            };
+            Some(SimpleMatch::new(
+                &format!("stop {}", project),
+                "media-playback-stop",
+                &action.to_string(),
+            ))
+        },
+        Box::new(NoopMatcher),
+    );
+
     matcher = matcher.with_child(
         SimpleMatch::new("start", "media-playback-start", ""),
-        get_start_matcher(),
+        get_start_matcher(context, config),
     );
 
     matcher = matcher.with_child(
         SimpleMatch::new("log", "format-justify-left", ""),
-        Box::new(CommandDisplayHandler::new("watson log -d -c")),
+        Box::new(CommandDisplayHandler::new(
+            context.runner.clone(),
+            "watson log -d -c",
+        )),
     );
 
     matcher = matcher.with_child(
         SimpleMatch::new("report", "document-properties", ""),
-        get_report_matcher(),
+        get_report_matcher(context),
     );
 
     Box::new(matcher)
 }
 
-fn get_start_matcher() -> Box<dyn Matcher> {
-    let known_tags = watson::get_tags();
-    let combinations = watson::get_project_tag_combinations();
+fn get_start_matcher(context: &Context, config: &Config) -> Box<dyn Matcher> {
+    let known_tags = watson::get_tags(context.runner.as_ref());
+    let combinations = watson::get_project_tag_combinations(context.runner.as_ref());
     let mut projects = combinations
         .iter()
         .map(|combo| combo.project.clone())
@@ -95,24 +152,150 @@ fn get_start_matcher() -> Box<dyn Matcher> {
         .collect::<Vec<_>>();
     projects.sort();
 
-    let matcher = ProjectMatcher::new(projects, known_tags, combinations);
+    let matcher = ProjectMatcher::new(context.clock.clone(), projects, known_tags, combinations)
+        .with_stdout(config.stdout);
     Box::new(matcher)
 }
 
-fn get_report_matcher() -> Box<dyn Matcher> {
+fn get_report_matcher(context: &Context) -> Box<dyn Matcher> {
     let matcher = StaticMatcher::new()
         .with_child(
             SimpleMatch::new("day", "view-calendar-day", ""),
-            Box::new(CommandDisplayHandler::new("watson report -d")),
+            Box::new(CommandDisplayHandler::new(
+                context.runner.clone(),
+                "watson report -d",
+            )),
         )
         .with_child(
             SimpleMatch::new("week", "view-calendar-week", ""),
-            Box::new(CommandDisplayHandler::new("watson report -w")),
+            Box::new(CommandDisplayHandler::new(
+                context.runner.clone(),
+                "watson report -w",
+            )),
         )
         .with_child(
             SimpleMatch::new("month", "view-calendar-month", ""),
-            Box::new(CommandDisplayHandler::new("watson report -m")),
+            Box::new(CommandDisplayHandler::new(
+                context.runner.clone(),
+                "watson report -m",
+            )),
         );
 
     Box::new(matcher)
 }
+
+#[cfg(test)]
+mod tests {
+    use matcher::testing::{Harness, assert_closed, assert_stdout, assert_titles, find, select};
+
+    use super::*;
+
+    fn harness() -> Harness {
+        Harness::new()
+            .with_command("watson tags", "coding\n")
+            .with_command("watson log --json", "[]")
+            .with_command("watson log -d -c", "Thursday 06 November 2025")
+            .with_command("watson stop", "")
+    }
+
+    #[test]
+    fn test_stop_only_when_tracking() {
+        let idle = harness().with_command("watson status", "No project started.");
+        assert_titles(
+            &*get_matcher(&idle.context(), &Config::default(), ""),
+            "",
+            &["start", "log", "report"],
+        );
+
+        let harness = harness().with_command(
+            "watson status",
+            "Project anyrun [coding] started 3 hours ago (2025.11.06 14:00:00-0500)",
+        );
+        let matcher = get_matcher(&harness.context(), &Config::default(), "");
+        assert_titles(&*matcher, "", &["stop anyrun", "start", "log", "report"]);
+
+        assert_closed(&select(&*matcher, "stop", "stop anyrun"));
+        harness.assert_ran("watson stop");
+    }
+
+    #[test]
+    fn test_notify_after_stop() {
+        let harness = harness()
+            .with_command(
+                "watson status",
+                "Project anyrun started 3 hours ago (2025.11.06 14:00:00-0500)",
+            )
+            .with_command("stub-notify", "");
+        let config = Config {
+            notify: true,
+            notifier: Some("stub-notify".into()),
+            ..Config::default()
+        };
+
+        let matcher = get_matcher(&harness.context(), &config, "");
+        assert_closed(&select(&*matcher, "stop", "stop anyrun"));
+        harness.assert_ran("stub-notify 'stop anyrun' 'Done'");
+    }
+
+    #[test]
+    fn test_history_recall() {
+        let harness = harness()
+            .with_command("watson status", "No project started.")
+            .with_command("watson start", "");
+        let query = "start anyrun +coding";
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), query);
+        assert_closed(&select(&*matcher, query, "anyrun +coding"));
+        // Stopping was recorded while tracking, and is not offered while idle
+        let stop = SimpleMatch::new("stop anyrun", "", "exec|watson stop");
+        History::for_plugin(&harness.context(), "watson")
+            .unwrap()
+            .with_query("stop")
+            .record(&stop)
+            .unwrap();
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "");
+        assert_titles(
+            &*matcher,
+            "",
+            &[query, "clear history", "start", "log", "report"],
+        );
+        let entry = find(&*matcher, "", query);
+        assert_eq!(entry.description, "exec|watson start anyrun +coding");
+    }
+
+    #[test]
+    fn test_stdout() {
+        let harness = harness().with_command(
+            "watson status",
+            "Project anyrun [coding] started 3 hours ago (2025.11.06 14:00:00-0500)",
+        );
+        let config = Config {
+            stdout: true,
+            ..Config::default()
+        };
+
+        let matcher = get_matcher(&harness.context(), &config, "");
+        assert_stdout(&select(&*matcher, "stop", "stop anyrun"), "anyrun");
+        assert_stdout(
+            &select(&*matcher, "start anyrun +coding", "anyrun +coding"),
+            "anyrun +coding",
+        );
+        let calls = harness.calls();
+        assert!(!calls.iter().any(|call| call.starts_with("watson start")));
+        assert!(!calls.iter().any(|call| call == "watson stop"));
+    }
+
+    #[test]
+    fn test_log_and_report() {
+        let harness = harness()
+            .with_command("watson status", "No project started.")
+            .with_command("watson report -w", "Total: 12h");
+
+        let matcher = get_matcher(&harness.context(), &Config::default(), "");
+        let log = find(&*matcher, "log", "watson log -d -c");
+        assert_eq!(log.description, "Thursday 06 November 2025");
+        let report = find(&*matcher, "report week", "watson report -w");
+        assert_eq!(report.description, "Total: 12h");
+    }
+}
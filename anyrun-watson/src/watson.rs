@@ -1,8 +1,7 @@
 use ::serde_json::from_str;
-use chrono::{Duration, Local};
+use matcher::{clock::Clock, date_expression::parse_duration, runner::CommandRunner};
 use serde::Deserialize;
 use std::collections::HashSet;
-use std::process::Command;
 
 #[derive(Debug, Deserialize)]
 struct Frame {
@@ -11,44 +10,21 @@ struct Frame {
 }
 
 /// Parse relative time (e.g., "1h30m") and convert to absolute time (HH:MM)
-pub fn parse_relative_time(relative: &str) -> Option<String> {
-    let relative = relative.to_lowercase();
-    let mut total_minutes = 0i64;
-
-    let mut current_num = String::new();
-    for ch in relative.chars() {
-        if ch.is_ascii_digit() {
-            current_num.push(ch);
-        } else if ch == 'h' {
-            if let Ok(hours) = current_num.parse::<i64>() {
-                total_minutes += hours * 60;
-            }
-            current_num.clear();
-        } else if ch == 'm' {
-            if let Ok(minutes) = current_num.parse::<i64>() {
-                total_minutes += minutes;
-            }
-            current_num.clear();
-        }
-    }
-
-    if total_minutes == 0 {
+pub fn parse_relative_time(clock: &dyn Clock, relative: &str) -> Option<String> {
+    let duration = parse_duration(relative)?;
+    if duration.is_zero() {
         return None;
     }
 
-    // Get current local time and subtract the offset
-    let now = Local::now();
-    let target_time = now - Duration::minutes(total_minutes);
-
     // Format as HH:MM for watson
+    let target_time = clock.now().checked_sub_signed(duration)?;
     Some(target_time.format("%H:%M").to_string())
 }
 
 /// Get all watson tags
-pub fn get_tags() -> Vec<String> {
-    Command::new("watson")
-        .arg("tags")
-        .output()
+pub fn get_tags(runner: &dyn CommandRunner) -> Vec<String> {
+    runner
+        .run("watson", &["tags"])
         .ok()
         .and_then(|o| String::from_utf8(o.stdout).ok())
         .map(|s| {
@@ -62,10 +38,9 @@ pub fn get_tags() -> Vec<String> {
 
 /// Get current active task information
 /// Returns (project, tags, elapsed_description) if active, None otherwise
-pub fn get_current_status() -> Option<(String, Vec<String>, String)> {
-    let output = Command::new("watson")
-        .arg("status")
-        .output()
+pub fn get_current_status(runner: &dyn CommandRunner) -> Option<(String, Vec<String>, String)> {
+    let output = runner
+        .run("watson", &["status"])
         .ok()
         .and_then(|o| String::from_utf8(o.stdout).ok())?;
 
@@ -136,12 +111,10 @@ impl ProjectTagCombo {
 
 /// Get unique project+tag combinations from watson history
 /// Uses recent frames to build a list of frequently used combinations
-pub fn get_project_tag_combinations() -> Vec<ProjectTagCombo> {
-    let output = Command::new("watson")
-        .arg("log")
-        .arg("--json")
-        .arg("-a") // Get all frames
-        .output()
+pub fn get_project_tag_combinations(runner: &dyn CommandRunner) -> Vec<ProjectTagCombo> {
+    // -a to get all frames
+    let output = runner
+        .run("watson", &["log", "--json", "-a"])
         .ok()
         .and_then(|o| String::from_utf8(o.stdout).ok());
 
@@ -171,3 +144,69 @@ pub fn get_project_tag_combinations() -> Vec<ProjectTagCombo> {
 
     Vec::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use matcher::{clock::FakeClock, runner::FakeRunner};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_relative_time() {
+        let clock = FakeClock::at("2025-11-06 00:10:00");
+        assert_eq!(
+            parse_relative_time(&clock, "30m"),
+            Some("23:40".to_string())
+        );
+        assert_eq!(
+            parse_relative_time(&clock, "1h5m"),
+            Some("23:05".to_string())
+        );
+        assert_eq!(parse_relative_time(&clock, "soon"), None);
+        assert_eq!(parse_relative_time(&clock, "99999999d"), None);
+    }
+
+    #[test]
+    fn test_get_current_status() {
+        let runner = FakeRunner::new();
+        runner.add_output(
+            "watson status",
+            0,
+            "Project anyrun-watson [coding, review] started 3 hours ago (2025.11.06 14:00:00-0500)",
+        );
+        let (project, tags, elapsed) = get_current_status(&runner).unwrap();
+        assert_eq!(project, "anyrun-watson");
+        assert_eq!(tags, vec!["coding", "review"]);
+        assert_eq!(elapsed, "3 hours ago");
+    }
+
+    #[test]
+    fn test_get_current_status_without_watson() {
+        let runner = FakeRunner::new();
+        assert_eq!(get_current_status(&runner), None);
+
+        runner.add_output("watson status", 0, "No project started.");
+        assert_eq!(get_current_status(&runner), None);
+    }
+
+    #[test]
+    fn test_get_project_tag_combinations() {
+        let runner = FakeRunner::new();
+        runner.add_output(
+            "watson log --json",
+            0,
+            r#"[
+                {"project": "anyrun", "tags": ["coding"]},
+                {"project": "client-x", "tags": []},
+                {"project": "anyrun", "tags": ["coding"]}
+            ]"#,
+        );
+        assert_eq!(
+            get_project_tag_combinations(&runner),
+            vec![
+                ProjectTagCombo::new("anyrun".into(), vec!["coding".into()]),
+                ProjectTagCombo::new("client-x".into(), vec![]),
+            ]
+        );
+    }
+}
@@ -1,37 +1,65 @@
+use std::sync::Arc;
+
 use anyrun_plugin::HandleResult;
-use matcher::{Matcher, SimpleMatch};
+use matcher::{
+    Matcher, SimpleMatch,
+    action::Action,
+    clock::Clock,
+    group::{Group, Groups},
+    normalize,
+    rank::{self, Ranking},
+};
 
 use crate::watson::{ProjectTagCombo, parse_relative_time};
 
+const NEW: &str = "New";
+const PROJECTS: &str = "Projects";
+const COMBINATIONS: &str = "Recent combinations";
+
 /// Matcher for creating new watson projects with optional tags.
 pub struct ProjectMatcher {
+    clock: Arc<dyn Clock>,
     known_projects: Vec<String>,
     known_tags: Vec<String>,
     combinations: Vec<ProjectTagCombo>,
+    stdout: bool,
 }
 
 impl ProjectMatcher {
     pub fn new(
+        clock: Arc<dyn Clock>,
         known_projects: Vec<String>,
         known_tags: Vec<String>,
         combinations: Vec<ProjectTagCombo>,
     ) -> Self {
         Self {
+            clock,
             known_projects,
             known_tags,
             combinations,
+            stdout: false,
         }
     }
+
+    /// Prints the project and tags instead of starting watson, for the
+    /// plugin's stdout mode.
+    pub fn with_stdout(mut self, stdout: bool) -> Self {
+        self.stdout = stdout;
+        self
+    }
 }
 
 /// Matcher for adding new tags to existing watson projects.
 pub struct TagMatcher {
     project: String,
     known_tags: Vec<String>,
+    stdout: bool,
 }
 
 pub struct TimeMatcher {
     project: String,
+    clock: Arc<dyn Clock>,
+    stdout: bool,
 }
 
 impl Matcher for ProjectMatcher {
@@ -45,12 +73,15 @@ impl Matcher for ProjectMatcher {
                 TagMatcher {
                     project: project.clone(),
                     known_tags: self.known_tags.clone(),
+                    stdout: self.stdout,
                 }
                 .get_matches(input.clone()),
             );
             matches.extend(
                 TimeMatcher {
                     project: project.clone(),
+                    clock: self.clock.clone(),
+                    stdout: self.stdout,
                 }
                 .get_matches(input),
             );
@@ -65,20 +96,41 @@ impl Matcher for ProjectMatcher {
             text = input.remove(0);
         }
 
-        let mut matches = Vec::new();
+        let mut groups = Groups::new()
+            .with_group(Group::new(NEW, "list-add"))
+            .with_group(Group::new(PROJECTS, "folder").with_limit(10))
+            .with_group(Group::new(COMBINATIONS, "document-open-recent").with_limit(5));
+
+        // Suggest known projects the text is a misspelling of, before a new
+        // project with that name is created by accident
+        if !self.known_projects.iter().any(|p| normalize::eq(p, text)) {
+            for project in &self.known_projects {
+                if !normalize::contains(project, text) && rank::is_typo_of(project, text) {
+                    let title = format!("did you mean {}?", project);
+                    let command = start_action(project, project, self.stdout);
+                    groups.push(NEW, SimpleMatch::new(&title, "", &command));
+                }
+            }
+        }
 
         // Add a wildcard match for new projects
         let title = format!("{} {}", text, "");
-        let command = format!("exec|watson start {}", text);
-        matches.push(SimpleMatch::new(&title, "", &command));
+        let command = start_action(text, text, self.stdout);
+        groups.push(NEW, SimpleMatch::new(&title, "", &command));
 
-        // Add matches for known projects
-        for project in &self.known_projects {
-            if project.contains(text) {
-                let title = format!("{} {}", project, "");
-                let command = format!("exec|watson start {}", project);
-                matches.push(SimpleMatch::new(&title, "", &command));
-            }
+        // Add matches for known projects, also by the initials of their words.
+        // Substring matches come first, so acronyms can't push them past the limit.
+        let ranking = Ranking::default().with_acronyms(true);
+        let mut projects = self
+            .known_projects
+            .iter()
+            .filter_map(|project| Some((ranking.rank(project, text)?, project)))
+            .collect::<Vec<_>>();
+        projects.sort_by_key(|(kind, _)| *kind);
+        for (_, project) in projects {
+            let title = format!("{} {}", project, "");
+            let command = start_action(project, project, self.stdout);
+            groups.push(PROJECTS, SimpleMatch::new(&title, "", &command));
         }
 
         // Add matches for known combinations of projects and tags
@@ -87,13 +139,13 @@ impl Matcher for ProjectMatcher {
             let tags: &Vec<String> = &combo.tags;
             let tags_str = tags.iter().map(|t| format!("+{} ", t)).collect::<String>();
             let title = format!("{} {}", project, tags_str);
-            if title.contains(text) {
-                let command = format!("exec|watson start {} {}", project, tags_str);
-                matches.push(SimpleMatch::new(&title, "", &command));
+            if normalize::contains(&title, text) {
+                let command = start_action(&title, &title, self.stdout);
+                groups.push(COMBINATIONS, SimpleMatch::new(&title, "", &command));
             }
         }
 
-        return matches;
+        return groups.into_matches();
     }
 
     fn handle(&self, _selection: SimpleMatch) -> HandleResult {
@@ -117,22 +169,20 @@ impl TagMatcher {
             .split_whitespace()
             .collect::<Vec<_>>()
             .join(" ");
-        let command = format!("exec|watson start {} {}", self.project, current_text);
+        let args = format!("{} {}", self.project, current_text);
+        let command = start_action(&args, &title, self.stdout);
         matches.push(SimpleMatch::new(&title, "", &command));
 
         // Add matches for known tags
         for tag in &self.known_tags {
             let tag = format!("+{}", tag);
-            if tag.contains(current_text) {
+            if normalize::contains(&tag, current_text) {
                 //? Split to deal with case where text is empty and we get double spaces
                 let title = format!("{} {} {}", self.project, text, tag)
                     .split_whitespace()
                     .collect::<Vec<_>>()
                     .join(" ");
-                let command = format!("exec|watson start {} {} {}", self.project, text, tag)
-                    .split_whitespace()
-                    .collect::<Vec<_>>()
-                    .join(" ");
+                let command = start_action(&title, &title, self.stdout);
                 matches.push(SimpleMatch::new(&title, "", &command));
             }
         }
@@ -153,15 +203,174 @@ impl TimeMatcher {
 
         // Parse time from current_text
         let time_str = current_text.trim_start_matches("-").trim();
-        let time = parse_relative_time(time_str);
+        let time = parse_relative_time(self.clock.as_ref(), time_str);
         let Some(time) = time else {
             return matches;
         };
 
         let title = format!("{} {} {}", self.project, text, current_text);
-        let command = format!("exec|watson start {} {} --at {}", self.project, text, time);
+        let args = format!("{} {} --at {}", self.project, text, time);
+        let project_and_tags = format!("{} {}", self.project, text);
+        let command = start_action(&args, &project_and_tags, self.stdout);
         matches.push(SimpleMatch::new(&title, "", &command));
 
         return matches;
     }
 }
+
+/// Description of a match starting watson with `args`, or printing the
+/// project and tags in `value` in stdout mode.
+fn start_action(args: &str, value: &str, stdout: bool) -> String {
+    let action = if stdout {
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        Action::Stdout(value)
+    } else {
+        Action::Exec(format!("watson start {}", args))
+    };
+    action.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use matcher::clock::FakeClock;
+    use matcher::testing::{assert_titles, find};
+
+    use super::*;
+
+    fn matcher() -> ProjectMatcher {
+        ProjectMatcher::new(
+            Arc::new(FakeClock::at("2025-11-06 17:00:00")),
+            vec!["anyrun".into(), "client-x".into()],
+            vec!["coding".into(), "meeting".into()],
+            vec![ProjectTagCombo::new(
+                "client-x".into(),
+                vec!["meeting".into()],
+            )],
+        )
+    }
+
+    #[test]
+    fn test_project_matches() {
+        let matcher = matcher();
+        assert_titles(&matcher, "anyr", &["New", "anyr ", "Projects", "anyrun "]);
+        assert_titles(
+            &matcher,
+            "client",
+            &[
+                "New",
+                "client ",
+                "Projects",
+                "client-x ",
+                "Recent combinations",
+                "client-x +meeting ",
+            ],
+        );
+
+        assert_titles(&matcher, "cx", &["New", "cx ", "Projects", "client-x "]);
+
+        let combo = find(&matcher, "client", "client-x +meeting ");
+        assert_eq!(combo.description, "exec|watson start client-x +meeting ");
+        assert_eq!(combo.icon, "document-open-recent");
+        let header = find(&matcher, "client", "Recent combinations");
+        assert_eq!(header.description, "");
+    }
+
+    #[test]
+    fn test_substring_matches_first() {
+        // Eleven projects match "ap" by their initials before "zapier" contains it
+        let mut projects = (0..11)
+            .map(|i| format!("ant-pie-{}", i))
+            .collect::<Vec<_>>();
+        projects.push("zapier".into());
+        let matcher = ProjectMatcher::new(
+            Arc::new(FakeClock::at("2025-11-06 17:00:00")),
+            projects,
+            vec![],
+            vec![],
+        );
+
+        let titles = matcher
+            .get_matches(vec!["ap"])
+            .into_iter()
+            .map(|m| m.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles[..4], ["New", "ap ", "Projects", "zapier "]);
+        assert_eq!(titles.len(), 3 + 10);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let matcher = matcher();
+        assert_titles(
+            &matcher,
+            "anyrn",
+            &["New", "did you mean anyrun?", "anyrn "],
+        );
+        assert_titles(
+            &matcher,
+            "clinet-x",
+            &["New", "did you mean client-x?", "clinet-x "],
+        );
+        assert_titles(
+            &matcher,
+            "anyrun",
+            &["New", "anyrun ", "Projects", "anyrun "],
+        );
+
+        let suggestion = find(&matcher, "anyrn", "did you mean anyrun?");
+        assert_eq!(suggestion.description, "exec|watson start anyrun");
+    }
+
+    #[test]
+    fn test_unicode_insensitive() {
+        let matcher = ProjectMatcher::new(
+            Arc::new(FakeClock::at("2025-11-06 17:00:00")),
+            vec!["Café-App".into()],
+            vec!["Réunion".into()],
+            vec![],
+        );
+        assert_titles(&matcher, "cafe", &["New", "cafe ", "Projects", "Café-App "]);
+        assert_titles(
+            &matcher,
+            "café-app +reu",
+            &["café-app +reu", "café-app +Réunion"],
+        );
+    }
+
+    #[test]
+    fn test_tag_matches() {
+        let matcher = matcher();
+        assert_titles(&matcher, "anyrun +co", &["anyrun +co", "anyrun +coding"]);
+        assert_titles(
+            &matcher,
+            "anyrun +coding +m",
+            &["anyrun +coding +m", "anyrun +coding +meeting"],
+        );
+
+        let tag = find(&matcher, "anyrun +coding +m", "anyrun +coding +meeting");
+        assert_eq!(tag.description, "exec|watson start anyrun +coding +meeting");
+    }
+
+    #[test]
+    fn test_time_matches() {
+        let matcher = matcher();
+
+        let time = find(&matcher, "anyrun -1h15m", "anyrun  -1h15m");
+        assert_eq!(time.description, "exec|watson start anyrun  --at 15:45");
+        assert_titles(&matcher, "anyrun -soon", &[]);
+    }
+
+    #[test]
+    fn test_stdout() {
+        let matcher = matcher().with_stdout(true);
+
+        let combo = find(&matcher, "client", "client-x +meeting ");
+        assert_eq!(combo.description, "stdout|client-x +meeting");
+        let suggestion = find(&matcher, "anyrn", "did you mean anyrun?");
+        assert_eq!(suggestion.description, "stdout|anyrun");
+        let tag = find(&matcher, "anyrun +coding +m", "anyrun +coding +meeting");
+        assert_eq!(tag.description, "stdout|anyrun +coding +meeting");
+        let time = find(&matcher, "anyrun +coding -1h15m", "anyrun +coding -1h15m");
+        assert_eq!(time.description, "stdout|anyrun +coding");
+    }
+}
@@ -0,0 +1,95 @@
+//! Lookups in a `StaticMatcher` with as many children as a menu plugin for
+//! emoji, man pages or bookmarks would have.
+use std::hint::black_box;
+
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, BenchmarkId, Criterion, criterion_group, criterion_main};
+use matcher::{Matcher, NoopMatcher, SimpleMatch, matcher_static::StaticMatcher};
+
+const WORDS: &[&str] = &[
+    "grinning", "face", "smiling", "heart", "cat", "dog", "party", "rocket", "thumbs", "sun",
+    "moon", "fire", "star", "tree", "flag", "café",
+];
+
+/// Distinct titles of three words and a number, e.g. "rocket face moon 1234".
+fn titles(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            let word = |shift: usize| WORDS[(i >> shift) % WORDS.len()];
+            format!("{} {} {} {}", word(0), word(4), word(8), i)
+        })
+        .collect()
+}
+
+fn matcher(n: usize) -> StaticMatcher {
+    titles(n)
+        .iter()
+        .fold(StaticMatcher::new(), |matcher, title| {
+            matcher.with_child(SimpleMatch::new(title, "", ""), Box::new(NoopMatcher))
+        })
+}
+
+fn bench_get_matches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_matches");
+    for n in [1_000, 10_000, 50_000] {
+        let matcher = matcher(n).with_max_results(20);
+        let exact = titles(n)[n / 2].clone();
+        let queries = [
+            ("common", "rocket"),
+            ("rare", "4242"),
+            ("exact", exact.as_str()),
+            ("none", "cafe moon xyz"),
+            ("short", "fi"),
+        ];
+        for (name, query) in queries {
+            bench_query(&mut group, &matcher, name, n, query);
+        }
+    }
+
+    // Typos and acronyms can't be looked up in the index
+    let n = 50_000;
+    let typos = matcher(n).with_max_results(20).with_typo_tolerance(true);
+    let acronyms = matcher(n).with_max_results(20).with_acronyms(true);
+    let queries = [
+        ("typos/common", &typos, "rocket"),
+        ("typos/typo", &typos, "rockte"),
+        ("typos/two", &typos, "rokcte"),
+        ("typos/short", &typos, "fi"),
+        ("typos/none", &typos, "xyzzy"),
+        ("acronyms/common", &acronyms, "rocket"),
+        ("acronyms/acronym", &acronyms, "rfm"),
+        ("acronyms/short", &acronyms, "fi"),
+        ("acronyms/none", &acronyms, "xyzzy"),
+    ];
+    for (name, matcher, query) in queries {
+        bench_query(&mut group, matcher, name, n, query);
+    }
+    group.finish();
+}
+
+/// Benchmarks `query` split into words, as anyrun passes it to plugins.
+fn bench_query(
+    group: &mut BenchmarkGroup<WallTime>,
+    matcher: &StaticMatcher,
+    name: &str,
+    n: usize,
+    query: &str,
+) {
+    group.bench_with_input(BenchmarkId::new(name, n), &query, |b, query| {
+        b.iter(|| matcher.get_matches(black_box(query.split_whitespace().collect())))
+    });
+}
+
+fn bench_build(c: &mut Criterion) {
+    let titles = titles(50_000);
+    c.bench_function("build 50000", |b| {
+        b.iter(|| {
+            titles.iter().fold(StaticMatcher::new(), |matcher, title| {
+                matcher.with_child(SimpleMatch::new(title, "", ""), Box::new(NoopMatcher))
+            })
+        })
+    });
+}
+
+criterion_group!(benches, bench_get_matches, bench_build);
+criterion_main!(benches);
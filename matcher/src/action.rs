@@ -0,0 +1,168 @@
+use std::fmt::{Display, Formatter};
+
+/// Separator between the actions of an encoded sequence.
+const SEQUENCE_SEPARATOR: &str = " ;; ";
+
+/// Action performed when a match is selected. Matches carry their action in
+/// the description, encoded as `<kind>|<value>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Run the command through `sh -c` and close.
+    Exec(String),
+    /// Copy the text to the clipboard.
+    Copy(String),
+    /// Print the text to stdout, for anyrun's dmenu-style usage.
+    Stdout(String),
+    /// Undo the N-th most recent change recorded in the journal.
+    Undo(usize),
+    /// Forget the commands recorded in the history.
+    ClearHistory,
+    /// Run several actions in order, encoded as `seq|<action> ;; <action>`, or as
+    /// `and|...` to stop at the first failing action. Only the result of the last
+    /// action reaches anyrun, so copying or printing should come last.
+    /// Sequences cannot be nested.
+    Sequence {
+        actions: Vec<Action>,
+        stop_on_failure: bool,
+    },
+}
+
+impl Action {
+    pub fn parse(description: &str) -> Option<Self> {
+        let (kind, value) = description.split_once('|')?;
+        let action = match kind {
+            "exec" => Action::Exec(value.to_string()),
+            "copy" => Action::Copy(value.to_string()),
+            "stdout" => Action::Stdout(value.to_string()),
+            "undo" => Action::Undo(value.parse().unwrap_or(1)),
+            "history" if value == "clear" => Action::ClearHistory,
+            "seq" | "and" => Action::Sequence {
+                actions: value
+                    .split(SEQUENCE_SEPARATOR)
+                    .map(Action::parse)
+                    .collect::<Option<_>>()?,
+                stop_on_failure: kind == "and",
+            },
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    /// Runs `actions` in order, stopping at the first failure if `stop_on_failure` is set.
+    pub fn sequence(actions: Vec<Action>, stop_on_failure: bool) -> Self {
+        Action::Sequence {
+            actions,
+            stop_on_failure,
+        }
+    }
+
+    /// Whether performing the action changes anything outside of anyrun.
+    pub fn has_side_effects(&self) -> bool {
+        match self {
+            Action::Exec(_) | Action::Undo(_) => true,
+            Action::Copy(_) | Action::Stdout(_) | Action::ClearHistory => false,
+            Action::Sequence { actions, .. } => actions.iter().any(Action::has_side_effects),
+        }
+    }
+
+    /// The action in stdout mode, where copied values are printed instead and
+    /// actions with side effects are dropped. Matches that should print more
+    /// than that use a stdout action in this mode. Returns `None` if nothing is
+    /// left to perform.
+    pub fn into_stdout(self) -> Option<Self> {
+        match self {
+            Action::Copy(value) | Action::Stdout(value) => Some(Action::Stdout(value)),
+            Action::Exec(_) | Action::Undo(_) => None,
+            Action::ClearHistory => Some(Action::ClearHistory),
+            Action::Sequence {
+                actions,
+                stop_on_failure,
+            } => {
+                let actions = actions
+                    .into_iter()
+                    .filter_map(Action::into_stdout)
+                    .collect::<Vec<_>>();
+                (!actions.is_empty()).then_some(Action::Sequence {
+                    actions,
+                    stop_on_failure,
+                })
+            }
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Exec(command) => write!(f, "exec|{}", command),
+            Action::Copy(text) => write!(f, "copy|{}", text),
+            Action::Stdout(text) => write!(f, "stdout|{}", text),
+            Action::Undo(n) => write!(f, "undo|{}", n),
+            Action::ClearHistory => write!(f, "history|clear"),
+            Action::Sequence {
+                actions,
+                stop_on_failure,
+            } => {
+                write!(f, "{}|", if *stop_on_failure { "and" } else { "seq" })?;
+                for (i, action) in actions.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(SEQUENCE_SEPARATOR)?;
+                    }
+                    write!(f, "{}", action)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let actions = [
+            Action::Exec("sed -i 's|a|b|' todos.txt".into()),
+            Action::Copy("2025_11_06".into()),
+            Action::Stdout("/home/user/project".into()),
+            Action::Undo(2),
+            Action::ClearHistory,
+            Action::sequence(
+                vec![
+                    Action::Exec("watson start anyrun".into()),
+                    Action::Exec("code ~/anyrun-plugins".into()),
+                ],
+                false,
+            ),
+            Action::sequence(
+                vec![
+                    Action::Exec("sed -i 's|a|b|' todos.txt".into()),
+                    Action::Copy("a".into()),
+                ],
+                true,
+            ),
+        ];
+        for action in actions {
+            assert_eq!(Action::parse(&action.to_string()), Some(action));
+        }
+        assert_eq!(Action::parse("plain description"), None);
+        assert_eq!(Action::parse("seq|exec|true ;; plain"), None);
+    }
+
+    #[test]
+    fn test_into_stdout() {
+        let exec = Action::Exec("sed -i 's|a|b|' todos.txt".into());
+        assert_eq!(exec.clone().into_stdout(), None);
+        assert_eq!(Action::Undo(1).into_stdout(), None);
+        assert_eq!(
+            Action::Copy("a".into()).into_stdout(),
+            Some(Action::Stdout("a".into()))
+        );
+        assert_eq!(
+            Action::sequence(vec![exec.clone(), Action::Copy("a".into())], true).into_stdout(),
+            Some(Action::sequence(vec![Action::Stdout("a".into())], true))
+        );
+        assert_eq!(Action::sequence(vec![exec], false).into_stdout(), None);
+    }
+}
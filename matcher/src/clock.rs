@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::{Tz, TzOffset};
+
+/// Source of the current time for matchers, in the zone local times are read
+/// and shown in.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Zone>;
+
+    /// Zone of the user, used instead of the host's `Local`.
+    fn zone(&self) -> Zone {
+        self.now().timezone()
+    }
+}
+
+/// Clock that reads the system time, in the system's zone.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Zone> {
+        Utc::now().with_timezone(&Zone::Local)
+    }
+}
+
+/// Clock that stands still at a given time until it is moved.
+pub struct FakeClock {
+    now: Mutex<DateTime<Zone>>,
+}
+
+impl FakeClock {
+    pub fn new(now: DateTime<Zone>) -> Self {
+        FakeClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Clock at a UTC time formatted as `%Y-%m-%d %H:%M:%S`.
+    pub fn at(time: &str) -> Self {
+        FakeClock::new(parse_in(time, Zone::Named(Tz::UTC)))
+    }
+
+    /// Sets the time, formatted as `%Y-%m-%d %H:%M:%S` in the clock's zone.
+    pub fn set(&self, time: &str) {
+        let mut now = self.now.lock().unwrap();
+        *now = parse_in(time, now.timezone());
+    }
+
+    /// Moves the clock to `zone`, keeping its local time.
+    pub fn set_zone(&self, zone: Zone) {
+        let mut now = self.now.lock().unwrap();
+        let time = now.naive_local().format(FAKE_TIME_FORMAT).to_string();
+        *now = parse_in(&time, zone);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Zone> {
+        *self.now.lock().unwrap()
+    }
+}
+
+const FAKE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn parse_in(time: &str, zone: Zone) -> DateTime<Zone> {
+    let naive = NaiveDateTime::parse_from_str(time, FAKE_TIME_FORMAT)
+        .unwrap_or_else(|e| panic!("invalid fake time '{}': {}", time, e));
+    zone.from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| panic!("fake time '{}' does not exist in {}", time, zone))
+}
+
+/// Time zone of a clock: the system's, or one from the time zone database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Zone::Local => f.write_str("local time"),
+            Zone::Named(tz) => write!(f, "{}", tz),
+        }
+    }
+}
+
+/// Offset of a `Zone`, shown like the offsets of the underlying zone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoneOffset {
+    Local(FixedOffset),
+    Named(TzOffset),
+}
+
+impl Offset for ZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        match self {
+            ZoneOffset::Local(offset) => *offset,
+            ZoneOffset::Named(offset) => offset.fix(),
+        }
+    }
+}
+
+impl Display for ZoneOffset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZoneOffset::Local(offset) => write!(f, "{}", offset),
+            ZoneOffset::Named(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl TimeZone for Zone {
+    type Offset = ZoneOffset;
+
+    fn from_offset(offset: &ZoneOffset) -> Self {
+        match offset {
+            ZoneOffset::Local(_) => Zone::Local,
+            ZoneOffset::Named(offset) => Zone::Named(Tz::from_offset(offset)),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Zone::Local => Local.offset_from_local_date(local).map(ZoneOffset::Local),
+            Zone::Named(tz) => tz.offset_from_local_date(local).map(ZoneOffset::Named),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Zone::Local => Local
+                .offset_from_local_datetime(local)
+                .map(ZoneOffset::Local),
+            Zone::Named(tz) => tz.offset_from_local_datetime(local).map(ZoneOffset::Named),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+        match self {
+            Zone::Local => ZoneOffset::Local(Local.offset_from_utc_date(utc)),
+            Zone::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_date(utc)),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+        match self {
+            Zone::Local => ZoneOffset::Local(Local.offset_from_utc_datetime(utc)),
+            Zone::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_datetime(utc)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_clock_zone() {
+        let clock = FakeClock::at("2025-11-06 17:00:00");
+        assert_eq!(clock.now().to_rfc3339(), "2025-11-06T17:00:00+00:00");
+
+        clock.set_zone(Zone::Named(chrono_tz::America::New_York));
+        assert_eq!(clock.now().to_rfc3339(), "2025-11-06T17:00:00-05:00");
+        assert_eq!(clock.now().format("%Z").to_string(), "EST");
+        clock.set("2025-07-01 09:30:00");
+        assert_eq!(clock.now().to_rfc3339(), "2025-07-01T09:30:00-04:00");
+    }
+}
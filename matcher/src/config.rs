@@ -0,0 +1,21 @@
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+/// Loads a plugin's configuration from `<config_dir>/<file>` in RON format.
+/// Falls back to the default configuration if the file is missing or invalid.
+pub fn load_config<T: DeserializeOwned + Default>(config_dir: &str, file: &str) -> T {
+    let path = Path::new(config_dir).join(file);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+
+    match ron::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to parse {}: {}", path.display(), e);
+            T::default()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::clock::{Clock, SystemClock};
+use crate::files::FileWriter;
+use crate::runner::{CommandRunner, DryRunRunner, SystemRunner};
+
+/// Environment variable that makes `Context::system` skip the commands and file
+/// writes of selected matches, used by `anyrun-plugin-cli --dry-run`.
+pub const DRY_RUN_ENV: &str = "ANYRUN_PLUGIN_DRY_RUN";
+
+/// Access to the outside world shared by the matchers of a plugin: processes,
+/// the clock, file writes and the home and state directories. Tests swap these for fakes,
+/// see `testing::Harness`.
+#[derive(Clone)]
+pub struct Context {
+    pub runner: Arc<dyn CommandRunner>,
+    pub clock: Arc<dyn Clock>,
+    pub files: FileWriter,
+    pub home_dir: Option<PathBuf>,
+    pub state_home: Option<PathBuf>,
+}
+
+impl Context {
+    pub fn system() -> Self {
+        let dry_run = std::env::var_os(DRY_RUN_ENV).is_some();
+        let runner: Arc<dyn CommandRunner> = if dry_run {
+            Arc::new(DryRunRunner::new(Arc::new(SystemRunner)))
+        } else {
+            Arc::new(SystemRunner)
+        };
+
+        Context {
+            runner,
+            clock: Arc::new(SystemClock),
+            files: FileWriter::new(dry_run),
+            home_dir: dirs::home_dir(),
+            state_home: dirs::state_dir().or_else(dirs::data_local_dir),
+        }
+    }
+
+    /// Directory where a plugin can persist its own state between runs.
+    pub fn state_dir(&self, plugin: &str) -> Option<PathBuf> {
+        Some(self.state_home.as_ref()?.join("anyrun").join(plugin))
+    }
+}
@@ -0,0 +1,391 @@
+//! Date expressions like `tomorrow`, `next friday 14:00`, `+3d`, `2 weeks ago`
+//! or `end of month`, evaluated against a clock so results are reproducible.
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Weekday,
+};
+
+use crate::clock::Clock;
+
+/// Formats of a time of day, after minutes are added to `9am`.
+const TIME_FORMATS: &[&str] = &["%H:%M", "%H:%M:%S", "%I:%M%p", "%I:%M %p"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl Unit {
+    fn parse(text: &str) -> Option<Self> {
+        let unit = match text {
+            "s" | "sec" | "secs" | "second" | "seconds" => Unit::Seconds,
+            "m" | "min" | "mins" | "minute" | "minutes" => Unit::Minutes,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Unit::Hours,
+            "d" | "day" | "days" => Unit::Days,
+            "w" | "wk" | "week" | "weeks" => Unit::Weeks,
+            "mo" | "month" | "months" => Unit::Months,
+            "y" | "yr" | "year" | "years" => Unit::Years,
+            _ => return None,
+        };
+        Some(unit)
+    }
+
+    /// Length of the unit, if it doesn't depend on the calendar.
+    fn duration(self) -> Option<Duration> {
+        match self {
+            Unit::Seconds => Some(Duration::seconds(1)),
+            Unit::Minutes => Some(Duration::minutes(1)),
+            Unit::Hours => Some(Duration::hours(1)),
+            Unit::Days => Some(Duration::days(1)),
+            Unit::Weeks => Some(Duration::weeks(1)),
+            Unit::Months | Unit::Years => None,
+        }
+    }
+}
+
+/// Evaluates a date expression in `zone`, relative to the clock's current time.
+///
+/// Supported are `now`, `today`, `tomorrow`, `yesterday`, weekdays optionally
+/// preceded by `next`, `last` or `this`, `next week`, offsets like `+3d`,
+/// `-1h30m`, `in 3 days` and `2 weeks ago`, and `start of` or `end of` a day,
+/// week, month or year. Dates may be followed by a time of day like `14:00` or
+/// `at 9am`; a time of day alone refers to today.
+pub fn parse_date_expression<Tz: TimeZone>(
+    clock: &dyn Clock,
+    text: &str,
+    zone: &Tz,
+) -> Option<DateTime<Tz>> {
+    let now = clock.now().with_timezone(zone);
+    let text = text.trim().to_lowercase();
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    if words.is_empty() {
+        return None;
+    }
+    if let Some(time) = parse_date(&now, &words) {
+        return Some(time);
+    }
+
+    // A time of day, possibly preceded by a date
+    for split in (words.len().saturating_sub(2)..words.len()).rev() {
+        let Some(time) = parse_time_of_day(&words[split..].join(" ")) else {
+            continue;
+        };
+        let mut date = &words[..split];
+        if let Some(rest) = date.strip_suffix(&["at"]) {
+            date = rest;
+        }
+        let date = if date.is_empty() {
+            now.date_naive()
+        } else {
+            parse_date(&now, date)?.date_naive()
+        };
+        return localize(zone, date.and_time(time));
+    }
+    None
+}
+
+/// Reads a duration like `30m`, `1h30m` or `2 hours`. Months and years are
+/// rejected since their length depends on the calendar.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let amounts = parse_amounts(&text)?;
+    amounts
+        .into_iter()
+        .try_fold(Duration::zero(), |total, (count, unit)| {
+            total.checked_add(&unit.duration()?.checked_mul(count.try_into().ok()?)?)
+        })
+}
+
+/// Moves `time` by an offset like `+45d`, `-1h30m` or `+ 2 weeks`.
+pub fn apply_offset<Tz: TimeZone>(time: &DateTime<Tz>, offset: &str) -> Option<DateTime<Tz>> {
+    let offset = offset.trim().to_lowercase();
+    let (sign, amount) = match offset.split_at_checked(1)? {
+        ("+", amount) => (1, amount),
+        ("-", amount) => (-1, amount),
+        _ => return None,
+    };
+    shift_all(time, sign, &parse_amounts(amount)?)
+}
+
+/// Reads a time of day like `14:00`, `9am` or `9:30 pm`.
+pub fn parse_time_of_day(text: &str) -> Option<NaiveTime> {
+    // chrono requires minutes, which are usually left out as in `9am`
+    let text = match text.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) if !text[..i].contains(':') => format!("{}:00{}", text[..i].trim(), &text[i..]),
+        _ => text.to_string(),
+    };
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(&text, format).ok())
+}
+
+/// Evaluates the expression without a time of day.
+fn parse_date<Tz: TimeZone>(now: &DateTime<Tz>, words: &[&str]) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    let midnight = |date: NaiveDate| localize(&zone, date.and_time(NaiveTime::MIN));
+
+    match words {
+        ["now"] => Some(now.clone()),
+        ["today"] => midnight(today),
+        ["tomorrow"] => midnight(today.succ_opt()?),
+        ["yesterday"] => midnight(today.pred_opt()?),
+        [weekday] | ["this", weekday] if weekday.parse::<Weekday>().is_ok() => {
+            let weekday = weekday.parse::<Weekday>().ok()?;
+            midnight(today + Days::new(days_until(today.weekday(), weekday)))
+        }
+        ["next", word] => match word.parse::<Weekday>() {
+            Ok(weekday) => {
+                let days = match days_until(today.weekday(), weekday) {
+                    0 => 7,
+                    days => days,
+                };
+                midnight(today + Days::new(days))
+            }
+            Err(_) => shift(now, 1, Unit::parse(word)?),
+        },
+        ["last", word] => match word.parse::<Weekday>() {
+            Ok(weekday) => {
+                let days = match days_until(weekday, today.weekday()) {
+                    0 => 7,
+                    days => days,
+                };
+                midnight(today - Days::new(days))
+            }
+            Err(_) => shift(now, -1, Unit::parse(word)?),
+        },
+        [edge @ ("start" | "beginning" | "end"), "of", unit]
+        | [edge @ ("start" | "beginning" | "end"), "of", "the", unit] => {
+            let (start, end) = period(today, Unit::parse(unit)?)?;
+            match *edge {
+                "end" => localize(&zone, end.and_hms_opt(23, 59, 59)?),
+                _ => midnight(start),
+            }
+        }
+        ["in", count, unit] => shift(now, count.parse().ok()?, Unit::parse(unit)?),
+        [count, unit, "ago"] => shift(now, -count.parse::<i64>().ok()?, Unit::parse(unit)?),
+        [compact, "ago"] => shift_all(now, -1, &parse_compact(compact)?),
+        ["in", compact] => shift_all(now, 1, &parse_compact(compact)?),
+        [offset] => apply_offset(now, offset),
+        _ => None,
+    }
+}
+
+/// Days from `from` until the next `to`, 0 if they are the same day.
+fn days_until(from: Weekday, to: Weekday) -> u64 {
+    (7 + to.num_days_from_monday() - from.num_days_from_monday()) as u64 % 7
+}
+
+/// First and last day of the day, week, month or year containing `date`.
+/// Weeks start on Monday.
+fn period(date: NaiveDate, unit: Unit) -> Option<(NaiveDate, NaiveDate)> {
+    let start = match unit {
+        Unit::Days => date,
+        Unit::Weeks => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        Unit::Months => date.with_day(1)?,
+        Unit::Years => date.with_ordinal(1)?,
+        _ => return None,
+    };
+    let end = match unit {
+        Unit::Days => start,
+        Unit::Weeks => start + Days::new(6),
+        Unit::Months => (start + Months::new(1)).pred_opt()?,
+        _ => (start + Months::new(12)).pred_opt()?,
+    };
+    Some((start, end))
+}
+
+/// Reads `3d`, `1h30m` or `2 weeks`.
+fn parse_amounts(text: &str) -> Option<Vec<(i64, Unit)>> {
+    match text.split_whitespace().collect::<Vec<_>>()[..] {
+        [compact] => parse_compact(compact),
+        [count, unit] => Some(vec![(count.parse().ok()?, Unit::parse(unit)?)]),
+        _ => None,
+    }
+}
+
+/// Reads amounts written without spaces, like `3d` or `1h30m`.
+fn parse_compact(text: &str) -> Option<Vec<(i64, Unit)>> {
+    let mut amounts = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let letters = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |i| digits + i);
+        if digits == 0 {
+            return None;
+        }
+        amounts.push((
+            rest[..digits].parse().ok()?,
+            Unit::parse(&rest[digits..letters])?,
+        ));
+        rest = &rest[letters..];
+    }
+    (!amounts.is_empty()).then_some(amounts)
+}
+
+fn shift_all<Tz: TimeZone>(
+    time: &DateTime<Tz>,
+    sign: i64,
+    amounts: &[(i64, Unit)],
+) -> Option<DateTime<Tz>> {
+    amounts
+        .iter()
+        .try_fold(time.clone(), |time, (count, unit)| {
+            shift(&time, sign * count, *unit)
+        })
+}
+
+/// Moves `time` by `count` units. Days and longer keep the time of day across
+/// daylight saving time changes.
+fn shift<Tz: TimeZone>(time: &DateTime<Tz>, count: i64, unit: Unit) -> Option<DateTime<Tz>> {
+    let naive = time.naive_local();
+    let naive = match unit {
+        Unit::Seconds | Unit::Minutes | Unit::Hours => {
+            return time
+                .clone()
+                .checked_add_signed(unit.duration()?.checked_mul(count.try_into().ok()?)?);
+        }
+        Unit::Days | Unit::Weeks => {
+            let days = unit.duration()?.num_days().checked_mul(count)?;
+            naive.checked_add_signed(Duration::try_days(days)?)?
+        }
+        Unit::Months | Unit::Years => {
+            let count = if unit == Unit::Years {
+                count.checked_mul(12)?
+            } else {
+                count
+            };
+            let months = Months::new(count.unsigned_abs().try_into().ok()?);
+            if count >= 0 {
+                naive.checked_add_months(months)?
+            } else {
+                naive.checked_sub_months(months)?
+            }
+        }
+    };
+    localize(&time.timezone(), naive)
+}
+
+fn localize<Tz: TimeZone>(zone: &Tz, naive: NaiveDateTime) -> Option<DateTime<Tz>> {
+    zone.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+
+    /// Evaluates at Thursday 2025-11-06 17:04:05 in the clock's zone.
+    fn parse(text: &str) -> Option<String> {
+        let clock = FakeClock::at("2025-11-06 17:04:05");
+        parse_date_expression(&clock, text, &clock.zone())
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    fn check(cases: &[(&str, &str)]) {
+        for (text, expected) in cases {
+            assert_eq!(
+                parse(text).as_deref(),
+                Some(*expected),
+                "expression '{}'",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_days() {
+        check(&[
+            ("now", "2025-11-06 17:04:05"),
+            ("today", "2025-11-06 00:00:00"),
+            ("Tomorrow", "2025-11-07 00:00:00"),
+            ("yesterday", "2025-11-05 00:00:00"),
+            ("friday", "2025-11-07 00:00:00"),
+            ("thursday", "2025-11-06 00:00:00"),
+            ("next thursday", "2025-11-13 00:00:00"),
+            ("next fri", "2025-11-07 00:00:00"),
+            ("last friday", "2025-10-31 00:00:00"),
+            ("last thursday", "2025-10-30 00:00:00"),
+        ]);
+    }
+
+    #[test]
+    fn test_times_of_day() {
+        check(&[
+            ("14:00", "2025-11-06 14:00:00"),
+            ("9am", "2025-11-06 09:00:00"),
+            ("9:30 pm", "2025-11-06 21:30:00"),
+            ("tomorrow 9am", "2025-11-07 09:00:00"),
+            ("next friday 14:00", "2025-11-07 14:00:00"),
+            ("monday at 8:15 am", "2025-11-10 08:15:00"),
+        ]);
+        assert_eq!(parse("25:00"), None);
+        assert_eq!(parse("soon 14:00"), None);
+    }
+
+    #[test]
+    fn test_offsets() {
+        check(&[
+            ("+3d", "2025-11-09 17:04:05"),
+            ("-1h30m", "2025-11-06 15:34:05"),
+            ("+1mo", "2025-12-06 17:04:05"),
+            ("in 3 days", "2025-11-09 17:04:05"),
+            ("in 90m", "2025-11-06 18:34:05"),
+            ("2 weeks ago", "2025-10-23 17:04:05"),
+            ("1y ago", "2024-11-06 17:04:05"),
+            ("next week", "2025-11-13 17:04:05"),
+            ("last month", "2025-10-06 17:04:05"),
+        ]);
+        assert_eq!(parse("+3"), None);
+        assert_eq!(parse("+3 lightyears"), None);
+        assert_eq!(parse("3 days"), None);
+    }
+
+    #[test]
+    fn test_periods() {
+        check(&[
+            ("end of month", "2025-11-30 23:59:59"),
+            ("start of month", "2025-11-01 00:00:00"),
+            ("end of the week", "2025-11-09 23:59:59"),
+            ("beginning of week", "2025-11-03 00:00:00"),
+            ("end of year", "2025-12-31 23:59:59"),
+            ("end of day", "2025-11-06 23:59:59"),
+        ]);
+    }
+
+    #[test]
+    fn test_apply_offset() {
+        let time = DateTime::parse_from_rfc3339("2025-01-31T12:00:00+01:00").unwrap();
+        let offset = |offset| apply_offset(&time, offset).map(|time| time.to_rfc3339());
+
+        assert_eq!(offset("+45d").as_deref(), Some("2025-03-17T12:00:00+01:00"));
+        assert_eq!(
+            offset("+ 1 month").as_deref(),
+            Some("2025-02-28T12:00:00+01:00")
+        );
+        assert_eq!(
+            offset("-1h30m").as_deref(),
+            Some("2025-01-31T10:30:00+01:00")
+        );
+        assert_eq!(offset("45d"), None);
+        assert_eq!(offset("+0000"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("1h5m"), Some(Duration::minutes(65)));
+        assert_eq!(parse_duration("2 hours"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration("1mo"), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::Path;
+
+/// Writes the files of matchers, such as their state and the files their
+/// actions change. On a dry run, writes are only printed, like the commands
+/// of `DryRunRunner`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileWriter {
+    dry_run: bool,
+}
+
+impl FileWriter {
+    pub fn new(dry_run: bool) -> Self {
+        FileWriter { dry_run }
+    }
+
+    /// Replaces the content of `path`, creating the file and its directory if
+    /// needed.
+    pub fn write(&self, path: &Path, content: &str) -> Result<(), String> {
+        if self.dry_run {
+            eprintln!("Dry run, not writing: {}", path.display());
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeRoot;
+
+    #[test]
+    fn test_write() {
+        let root = FakeRoot::new();
+        let path = root.state().join("anyrun/todo/journal.jsonl");
+
+        FileWriter::new(true).write(&path, "dry").unwrap();
+        assert!(!path.exists());
+
+        FileWriter::default().write(&path, "written").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "written");
+    }
+}
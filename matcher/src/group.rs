@@ -0,0 +1,114 @@
+//! Sections of results, each listed under a header match.
+use crate::SimpleMatch;
+
+/// A section of results, shown under a header with the group's title.
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub title: String,
+    pub icon: String,
+    /// Groups are listed by increasing order, then in the order they were added.
+    pub order: i32,
+    /// Maximum number of matches listed in the group.
+    pub limit: usize,
+}
+
+impl Group {
+    pub fn new(title: &str, icon: &str) -> Self {
+        Group {
+            title: title.to_string(),
+            icon: icon.to_string(),
+            order: 0,
+            limit: usize::MAX,
+        }
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Match shown above the results of the group. It has no action, so
+    /// selecting it does nothing.
+    pub fn header(&self) -> SimpleMatch {
+        SimpleMatch::new(&self.title, &self.icon, "")
+    }
+}
+
+/// Matches sorted into groups, laid out as a flat list of headers and matches.
+#[derive(Default)]
+pub struct Groups {
+    groups: Vec<(Group, Vec<SimpleMatch>)>,
+}
+
+impl Groups {
+    pub fn new() -> Self {
+        Groups::default()
+    }
+
+    pub fn with_group(mut self, group: Group) -> Self {
+        self.groups.push((group, Vec::new()));
+        self
+    }
+
+    /// Adds a match to the group titled `group`, which is created with default
+    /// settings if it doesn't exist. Matches without an icon get the group's.
+    pub fn push(&mut self, group: &str, mut simple_match: SimpleMatch) {
+        let index = match self.groups.iter().position(|(g, _)| g.title == group) {
+            Some(index) => index,
+            None => {
+                self.groups.push((Group::new(group, ""), Vec::new()));
+                self.groups.len() - 1
+            }
+        };
+
+        let (group, matches) = &mut self.groups[index];
+        if simple_match.icon.is_empty() {
+            simple_match.icon = group.icon.clone();
+        }
+        matches.push(simple_match);
+    }
+
+    /// Headers followed by the matches of their group, skipping empty groups.
+    pub fn into_matches(mut self) -> Vec<SimpleMatch> {
+        self.groups.sort_by_key(|(group, _)| group.order);
+
+        let mut result = Vec::new();
+        for (group, matches) in self.groups {
+            if matches.is_empty() {
+                continue;
+            }
+            result.push(group.header());
+            result.extend(matches.into_iter().take(group.limit));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_matches() {
+        let mut groups = Groups::new()
+            .with_group(Group::new("Projects", "folder").with_limit(2))
+            .with_group(Group::new("New", "list-add").with_order(-1))
+            .with_group(Group::new("Empty", ""));
+        for project in ["a", "b", "c"] {
+            groups.push("Projects", SimpleMatch::new(project, "", ""));
+        }
+        groups.push("New", SimpleMatch::new("d", "document-new", ""));
+        groups.push("Other", SimpleMatch::new("e", "", ""));
+
+        let matches = groups.into_matches();
+        let titles = matches.iter().map(|m| m.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, ["New", "d", "Projects", "a", "b", "Other", "e"]);
+        assert_eq!(matches[1].icon, "document-new");
+        assert_eq!(matches[3].icon, "folder");
+    }
+}
@@ -1,17 +1,19 @@
-use std::process::Command;
+use std::sync::Arc;
 
 use anyrun_plugin::HandleResult;
 
-use crate::{Matcher, SimpleMatch};
+use crate::{Matcher, SimpleMatch, runner::CommandRunner};
 
 /// Basic copy handler that runs the matched description as a command.
 pub struct CommandDisplayHandler {
+    runner: Arc<dyn CommandRunner>,
     command: String,
 }
 
 impl CommandDisplayHandler {
-    pub fn new(command: &str) -> Self {
+    pub fn new(runner: Arc<dyn CommandRunner>, command: &str) -> Self {
         CommandDisplayHandler {
+            runner,
             command: command.to_string(),
         }
     }
@@ -20,7 +22,7 @@ impl CommandDisplayHandler {
 impl Matcher for CommandDisplayHandler {
     fn get_matches(&self, _text: Vec<&str>) -> Vec<SimpleMatch> {
         let cmd = &self.command;
-        let output = Command::new("sh").arg("-c").arg(cmd).output();
+        let output = self.runner.shell(cmd);
 
         let output = match output {
             Ok(output) => output,
@@ -44,3 +46,28 @@ impl Matcher for CommandDisplayHandler {
         HandleResult::Close
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Harness, query};
+
+    #[test]
+    fn test_displays_command_output() {
+        let harness = Harness::new().with_command("watson report -d", "2h 30m");
+        let handler = CommandDisplayHandler::new(harness.context().runner, "watson report -d");
+        let matches = query(&handler, "");
+        assert_eq!(
+            matches,
+            vec![SimpleMatch::new("watson report -d", "", "2h 30m")]
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_command() {
+        let harness = Harness::new();
+        let handler = CommandDisplayHandler::new(harness.context().runner, "watson report -d");
+        let matches = query(&handler, "");
+        assert_eq!(matches[0].title, "error");
+    }
+}
@@ -0,0 +1,45 @@
+use anyrun_plugin::HandleResult;
+
+use crate::{Matcher, SimpleMatch, journal::Journal};
+
+/// Handler that lists the recorded changes of a journal as undoable matches.
+/// A number as input selects the N-th most recent change directly.
+pub struct UndoHandler {
+    journal: Journal,
+    max_results: usize,
+}
+
+impl UndoHandler {
+    pub fn new(journal: Journal) -> Self {
+        UndoHandler {
+            journal,
+            max_results: 10,
+        }
+    }
+}
+
+impl Matcher for UndoHandler {
+    fn get_matches(&self, input: Vec<&str>) -> Vec<SimpleMatch> {
+        let selected = input.first().and_then(|text| text.parse::<usize>().ok());
+
+        self.journal
+            .entries()
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (i + 1, entry))
+            .filter(|(n, _)| selected.is_none_or(|selected| selected == *n))
+            .take(self.max_results)
+            .map(|(n, entry)| {
+                SimpleMatch::new(
+                    &format!("undo {}: {}", n, entry.summary()),
+                    "edit-undo",
+                    &format!("undo|{}", n),
+                )
+            })
+            .collect()
+    }
+
+    fn handle(&self, _selection: SimpleMatch) -> HandleResult {
+        HandleResult::Close
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{SimpleMatch, clock::Clock, context::Context, files::FileWriter};
+
+/// Maximum number of entries kept in a history, older entries are dropped.
+const MAX_ENTRIES: usize = 20;
+
+/// A selected match whose command ran successfully.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Query typed for the match, without the plugin prefix.
+    #[serde(default)]
+    pub query: String,
+    pub title: String,
+    pub description: String,
+    pub timestamp: i64,
+}
+
+impl HistoryEntry {
+    /// Query the entry is offered as, the title for entries recorded before
+    /// queries were.
+    pub fn recalled_query(&self) -> &str {
+        if self.query.is_empty() {
+            &self.title
+        } else {
+            &self.query
+        }
+    }
+}
+
+/// The last query a plugin matched, kept in its state for the history, since
+/// anyrun only passes the selected match to the handler.
+#[derive(Default)]
+pub struct LastQuery(Mutex<String>);
+
+impl LastQuery {
+    pub fn set(&self, query: &str) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = query.to_string();
+    }
+
+    pub fn get(&self) -> String {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Commands a plugin ran, so they can be offered again without retyping the query.
+#[derive(Clone)]
+pub struct History {
+    path: PathBuf,
+    clock: Arc<dyn Clock>,
+    files: FileWriter,
+    query: String,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>, clock: Arc<dyn Clock>) -> Self {
+        History {
+            path: path.into(),
+            clock,
+            files: FileWriter::default(),
+            query: String::new(),
+        }
+    }
+
+    /// History stored in the state directory of the given plugin.
+    pub fn for_plugin(context: &Context, plugin: &str) -> Option<Self> {
+        let path = context.state_dir(plugin)?.join("history.jsonl");
+        Some(History::new(path, context.clock.clone()).with_files(context.files))
+    }
+
+    /// Writer used for the history file.
+    pub fn with_files(mut self, files: FileWriter) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Query typed for the selections recorded next. Selections made without
+    /// one, such as recalled entries, keep the query they were recorded with.
+    pub fn with_query(mut self, query: &str) -> Self {
+        self.query = query.to_string();
+        self
+    }
+
+    /// Recorded entries, most recent first. Each command appears once.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let mut entries = self.read_entries();
+        entries.reverse();
+        entries
+    }
+
+    /// Records the selected match, replacing earlier entries with the same command.
+    pub fn record(&self, selection: &SimpleMatch) -> Result<(), String> {
+        let mut entries = self.read_entries();
+        let position = entries
+            .iter()
+            .position(|entry| entry.description == selection.description);
+        let query = match position.map(|i| entries.remove(i)) {
+            Some(entry) if self.query.is_empty() => entry.recalled_query().to_string(),
+            _ => self.query.clone(),
+        };
+        entries.push(HistoryEntry {
+            query,
+            title: selection.title.clone(),
+            description: selection.description.clone(),
+            timestamp: self.clock.now().timestamp(),
+        });
+        self.write_entries(&entries)
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        self.write_entries(&[])
+    }
+
+    fn read_entries(&self) -> Vec<HistoryEntry> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    fn write_entries(&self, entries: &[HistoryEntry]) -> Result<(), String> {
+        let skip = entries.len().saturating_sub(MAX_ENTRIES);
+        let mut content = String::new();
+        for entry in &entries[skip..] {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        self.files.write(&self.path, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn test_record_deduplicates() {
+        let harness = Harness::new();
+        let history = History::for_plugin(&harness.context(), "watson").unwrap();
+
+        for title in ["start anyrun", "stop", "start anyrun"] {
+            let command = format!("exec|watson {}", title);
+            history
+                .record(&SimpleMatch::new(title, "", &command))
+                .unwrap();
+        }
+        let titles = history
+            .entries()
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["start anyrun", "stop"]);
+
+        history.clear().unwrap();
+        assert_eq!(history.entries(), []);
+    }
+
+    #[test]
+    fn test_record_query() {
+        let harness = Harness::new();
+        let history = History::for_plugin(&harness.context(), "watson").unwrap();
+        let start = SimpleMatch::new("anyrun +coding", "", "exec|watson start anyrun +coding");
+
+        history
+            .clone()
+            .with_query("start anyrun +c")
+            .record(&start)
+            .unwrap();
+        // Recalling the entry doesn't replace its query with the empty one
+        history.record(&start).unwrap();
+        let entries = history.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].recalled_query(), "start anyrun +c");
+
+        let stop = SimpleMatch::new("stop", "", "exec|watson stop");
+        history.record(&stop).unwrap();
+        assert_eq!(history.entries()[0].recalled_query(), "stop");
+    }
+}
@@ -0,0 +1,154 @@
+//! Indexes over normalized keys, to find the keys containing a text, or
+//! within a few typos of it, without comparing it with every one of them.
+use std::collections::HashMap;
+
+/// Number of characters per n-gram. Texts shorter than this can't be looked up.
+const N: usize = 3;
+
+#[derive(Default)]
+pub struct NgramIndex {
+    postings: HashMap<[char; N], Vec<usize>>,
+}
+
+impl NgramIndex {
+    pub fn new() -> Self {
+        NgramIndex::default()
+    }
+
+    /// Adds the normalized `key` under `id`. Ids must be added in increasing order.
+    pub fn insert(&mut self, id: usize, key: &str) {
+        let chars = key.chars().collect::<Vec<_>>();
+        for gram in chars.windows(N) {
+            let gram = <[char; N]>::try_from(gram).unwrap();
+            let ids = self.postings.entry(gram).or_default();
+            if ids.last() != Some(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    /// Ids of the keys containing every n-gram of the normalized `text`, in
+    /// increasing order. These are the only keys that can contain `text`, but
+    /// not all of them do. Returns `None` if `text` is too short to look up.
+    pub fn candidates<'a>(&'a self, text: &str) -> Option<impl Iterator<Item = usize> + 'a> {
+        let chars = text.chars().collect::<Vec<_>>();
+        if chars.len() < N {
+            return None;
+        }
+
+        let mut postings = chars
+            .windows(N)
+            .map(|gram| {
+                let gram = <[char; N]>::try_from(gram).unwrap();
+                self.postings.get(&gram).map_or(&[][..], Vec::as_slice)
+            })
+            .collect::<Vec<_>>();
+
+        // Intersect lazily, starting from the rarest n-gram
+        postings.sort_by_key(|ids| ids.len());
+        let rarest = postings.remove(0);
+        let candidates = rarest
+            .iter()
+            .copied()
+            .filter(move |id| postings.iter().all(|ids| ids.binary_search(id).is_ok()));
+        Some(candidates)
+    }
+}
+
+/// Number of leading characters of keys in a `HeadIndex`.
+const HEAD: usize = 8;
+
+/// Index of the first characters of keys by their place, to find the keys
+/// whose beginning may be within a few typos of a text.
+#[derive(Default)]
+pub struct HeadIndex {
+    /// Ids of the keys with each character at each place.
+    postings: Vec<HashMap<char, Vec<usize>>>,
+    len: usize,
+}
+
+impl HeadIndex {
+    pub fn new() -> Self {
+        HeadIndex::default()
+    }
+
+    /// Adds the normalized `key` under `id`. Ids must be added in increasing order.
+    pub fn insert(&mut self, id: usize, key: &str) {
+        for (place, c) in key.chars().take(HEAD).enumerate() {
+            if place == self.postings.len() {
+                self.postings.push(HashMap::new());
+            }
+            self.postings[place].entry(c).or_default().push(id);
+        }
+        self.len = id + 1;
+    }
+
+    /// Ids of the keys whose beginning may be within `max` typos of the
+    /// normalized `text`, in increasing order. A character of the text that is
+    /// not mistyped is at most `max` places away from its place in such a key,
+    /// so all but `max` of the first characters of the text are found there.
+    pub fn candidates(&self, text: &str, max: usize) -> Vec<usize> {
+        // Only characters whose places around are all indexed are looked up
+        let chars = text.chars().take(HEAD - max).collect::<Vec<_>>();
+
+        // Number of characters found in each key, and the last one found there
+        // in case it is found twice
+        let mut found = vec![0; self.len];
+        let mut last = vec![usize::MAX; self.len];
+        for (i, c) in chars.iter().enumerate() {
+            for place in i.saturating_sub(max)..=i + max {
+                let Some(ids) = self.postings.get(place).and_then(|p| p.get(c)) else {
+                    continue;
+                };
+                for &id in ids {
+                    if last[id] != i {
+                        last[id] = i;
+                        found[id] += 1;
+                    }
+                }
+            }
+        }
+
+        let needed = chars.len().saturating_sub(max);
+        (0..self.len).filter(|&id| found[id] >= needed).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let mut index = NgramIndex::new();
+        for (id, key) in ["grinning face", "smiling face", "face palm", "cat"]
+            .iter()
+            .enumerate()
+        {
+            index.insert(id, key);
+        }
+
+        let candidates = |text| index.candidates(text).map(Iterator::collect::<Vec<_>>);
+        assert_eq!(candidates("face"), Some(vec![0, 1, 2]));
+        assert_eq!(candidates("ing fa"), Some(vec![0, 1]));
+        assert_eq!(candidates("dog"), Some(vec![]));
+        assert_eq!(candidates("ca"), None);
+    }
+
+    #[test]
+    fn test_head_candidates() {
+        let mut index = HeadIndex::new();
+        for (id, key) in ["anyrun", "nayrun", "watson", "anyrun-plugins-experimental"]
+            .iter()
+            .enumerate()
+        {
+            index.insert(id, key);
+        }
+
+        assert_eq!(index.candidates("anyrn", 1), [0, 1, 3]);
+        assert_eq!(index.candidates("anyrun-plug", 2), [0, 1, 3]);
+        assert_eq!(index.candidates("anyrun", 0), [0, 3]);
+        assert_eq!(index.candidates("wtasno", 1), [2]);
+        assert_eq!(index.candidates("nosawt", 2), Vec::<usize>::new());
+    }
+}
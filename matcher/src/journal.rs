@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::Clock, context::Context, files::FileWriter};
+
+/// Maximum number of entries kept in a journal, older entries are dropped.
+const MAX_ENTRIES: usize = 50;
+
+/// A single recorded mutation of a file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub file: PathBuf,
+    pub before: String,
+    pub after: String,
+    pub timestamp: i64,
+}
+
+impl JournalEntry {
+    /// Short human readable description of the change.
+    pub fn summary(&self) -> String {
+        let (_, removed, added) = self.hunk();
+        match (removed.as_slice(), added.as_slice()) {
+            ([], [added]) => format!("added '{}'", added.trim()),
+            ([removed], []) => format!("removed '{}'", removed.trim()),
+            ([removed], [added]) => format!("'{}' -> '{}'", removed.trim(), added.trim()),
+            (removed, added) => format!("-{} +{} lines", removed.len(), added.len()),
+        }
+    }
+
+    /// Lines removed and added by this change, together with the line preceding
+    /// them in `before` (if any).
+    fn hunk(&self) -> (Option<&str>, Vec<&str>, Vec<&str>) {
+        let before = self.before.lines().collect::<Vec<_>>();
+        let after = self.after.lines().collect::<Vec<_>>();
+
+        let prefix = before
+            .iter()
+            .zip(&after)
+            .take_while(|(b, a)| b == a)
+            .count();
+        let suffix = before[prefix..]
+            .iter()
+            .rev()
+            .zip(after[prefix..].iter().rev())
+            .take_while(|(b, a)| b == a)
+            .count();
+
+        let context = prefix.checked_sub(1).map(|i| before[i]);
+        let removed = before[prefix..before.len() - suffix].to_vec();
+        let added = after[prefix..after.len() - suffix].to_vec();
+        (context, removed, added)
+    }
+
+    /// Reverts this change in `current`, returning the new content. Returns `None`
+    /// if the file has changed in a way that makes the change impossible to locate.
+    fn revert(&self, current: &str) -> Option<String> {
+        if current == self.after {
+            return Some(self.before.clone());
+        }
+
+        let (context, removed, added) = self.hunk();
+        let mut lines = current.lines().collect::<Vec<_>>();
+
+        let position = if added.is_empty() {
+            match context {
+                Some(context) => lines.iter().position(|l| *l == context)? + 1,
+                None => 0,
+            }
+        } else {
+            let position = lines
+                .windows(added.len())
+                .position(|window| window == added.as_slice())?;
+            lines.drain(position..position + added.len());
+            position
+        };
+        lines.splice(position..position, removed);
+
+        let mut reverted = lines.join("\n");
+        if current.ends_with('\n') || (current.is_empty() && self.before.ends_with('\n')) {
+            reverted.push('\n');
+        }
+        Some(reverted)
+    }
+}
+
+/// Log of the file mutations made by a plugin, used to undo them.
+///
+/// Files registered with `tracking` are snapshotted before a command runs and
+/// any difference afterwards is recorded as an entry.
+#[derive(Clone)]
+pub struct Journal {
+    path: PathBuf,
+    tracked: Vec<PathBuf>,
+    clock: Arc<dyn Clock>,
+    files: FileWriter,
+}
+
+impl Journal {
+    pub fn new(path: impl Into<PathBuf>, clock: Arc<dyn Clock>) -> Self {
+        Journal {
+            path: path.into(),
+            tracked: Vec::new(),
+            clock,
+            files: FileWriter::default(),
+        }
+    }
+
+    /// Journal stored in the state directory of the given plugin.
+    pub fn for_plugin(context: &Context, plugin: &str) -> Option<Self> {
+        let path = context.state_dir(plugin)?.join("journal.jsonl");
+        Some(Journal::new(path, context.clock.clone()).with_files(context.files))
+    }
+
+    /// Writer used for the journal and for reverting tracked files.
+    pub fn with_files(mut self, files: FileWriter) -> Self {
+        self.files = files;
+        self
+    }
+
+    pub fn tracking(mut self, file: impl AsRef<Path>) -> Self {
+        self.tracked.push(file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Recorded entries, most recent first.
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        let mut entries = self.read_entries();
+        entries.reverse();
+        entries
+    }
+
+    /// Current content of every tracked file.
+    pub fn snapshot(&self) -> Vec<(PathBuf, String)> {
+        self.tracked
+            .iter()
+            .map(|file| (file.clone(), fs::read_to_string(file).unwrap_or_default()))
+            .collect()
+    }
+
+    /// Records an entry for every tracked file that differs from `snapshot`.
+    pub fn record_changes(&self, snapshot: Vec<(PathBuf, String)>) -> Result<(), String> {
+        for (file, before) in snapshot {
+            let after = fs::read_to_string(&file).unwrap_or_default();
+            if before != after {
+                self.record(&file, &before, &after)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, file: &Path, before: &str, after: &str) -> Result<(), String> {
+        let mut entries = self.read_entries();
+        entries.push(JournalEntry {
+            file: file.to_path_buf(),
+            before: before.to_string(),
+            after: after.to_string(),
+            timestamp: self.clock.now().timestamp(),
+        });
+        self.write_entries(&entries)
+    }
+
+    /// Reverts the `n`-th most recent change (starting at 1) and removes it from the journal.
+    pub fn undo(&self, n: usize) -> Result<JournalEntry, String> {
+        let mut entries = self.read_entries();
+        if n == 0 || n > entries.len() {
+            return Err(format!("No change #{} to undo", n));
+        }
+
+        let entry = entries.remove(entries.len() - n);
+        let current = fs::read_to_string(&entry.file)
+            .map_err(|e| format!("Failed to read {}: {}", entry.file.display(), e))?;
+        let reverted = entry.revert(&current).ok_or_else(|| {
+            format!(
+                "{} has changed too much to undo {}",
+                entry.file.display(),
+                entry.summary()
+            )
+        })?;
+        self.files.write(&entry.file, &reverted)?;
+
+        self.write_entries(&entries)?;
+        Ok(entry)
+    }
+
+    fn read_entries(&self) -> Vec<JournalEntry> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    fn write_entries(&self, entries: &[JournalEntry]) -> Result<(), String> {
+        let skip = entries.len().saturating_sub(MAX_ENTRIES);
+        let mut content = String::new();
+        for entry in &entries[skip..] {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        self.files.write(&self.path, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(before: &str, after: &str) -> JournalEntry {
+        JournalEntry {
+            file: PathBuf::from("todos.txt"),
+            before: before.into(),
+            after: after.into(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_revert_replaced_line() {
+        let entry = entry("- [ ] a\n- [ ] b\n", "- [ ] a\n- [x] b\n");
+        assert_eq!(entry.summary(), "'- [ ] b' -> '- [x] b'");
+
+        // A later change to another line must survive the undo.
+        let current = "- [x] a\n- [x] b\n";
+        assert_eq!(entry.revert(current).unwrap(), "- [x] a\n- [ ] b\n");
+    }
+
+    #[test]
+    fn test_revert_appended_line() {
+        let entry = entry("- [ ] a\n", "- [ ] a\n- [ ] b\n");
+        assert_eq!(entry.summary(), "added '- [ ] b'");
+
+        let current = "- [ ] a\n- [ ] b\n- [ ] c\n";
+        assert_eq!(entry.revert(current).unwrap(), "- [ ] a\n- [ ] c\n");
+        assert_eq!(entry.revert("- [ ] a\n"), None);
+    }
+}
@@ -1,7 +1,25 @@
+pub mod action;
+pub mod clock;
+pub mod config;
+pub mod context;
+pub mod date_expression;
+pub mod files;
+pub mod group;
 pub mod handler_display;
 pub mod handler_display_command;
+pub mod handler_undo;
 pub mod handler_wildcard_command;
+pub mod history;
+pub mod index;
+pub mod journal;
 pub mod matcher_static;
+pub mod normalize;
+pub mod notifier;
+pub mod predicate;
+pub mod rank;
+pub mod runner;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 use abi_stable::std_types::ROption;
 use anyrun_plugin::{HandleResult, Match};
 
@@ -27,6 +45,8 @@ pub struct SimpleMatch {
     pub title: String,
     pub icon: String,
     pub description: String,
+    /// Whether the title is Pango markup, which anyrun renders styled.
+    pub use_pango: bool,
 }
 
 impl SimpleMatch {
@@ -35,8 +55,14 @@ impl SimpleMatch {
             title: title.to_string(),
             icon: icon.to_string(),
             description: description.to_string(),
+            use_pango: false,
         }
     }
+
+    pub fn with_pango(mut self, use_pango: bool) -> Self {
+        self.use_pango = use_pango;
+        self
+    }
 }
 
 impl From<SimpleMatch> for Match {
@@ -55,7 +81,7 @@ impl From<SimpleMatch> for Match {
             title: simple_match.title.into(),
             icon,
             description,
-            use_pango: false,
+            use_pango: simple_match.use_pango,
             id: ROption::RNone,
         }
     }
@@ -67,6 +93,7 @@ impl From<Match> for SimpleMatch {
             title: m.title.to_string(),
             icon: m.icon.unwrap_or_default().to_string(),
             description: m.description.unwrap_or_default().to_string(),
+            use_pango: m.use_pango,
         }
     }
 }
@@ -1,23 +1,121 @@
-use std::process::Command;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 
 use anyrun_plugin::HandleResult;
 
-use crate::{Matcher, SimpleMatch};
+use crate::{
+    Matcher, SimpleMatch,
+    action::Action,
+    history::History,
+    index::{HeadIndex, NgramIndex},
+    journal::Journal,
+    normalize::normalize,
+    notifier::Notifier,
+    predicate::Predicate,
+    rank::{Key, Ranking, max_typos},
+    runner::{CommandRunner, SystemRunner},
+};
+
+/// Number of history entries offered for an empty query.
+const HISTORY_SHOWN: usize = 5;
 
 pub struct StaticMatcher {
     children: Vec<(SimpleMatch, Box<dyn Matcher>)>,
+    /// Normalized keys of the children, by position.
+    keys: Vec<Key>,
+    /// Positions of the children with a given normalized key.
+    exact: HashMap<String, Vec<usize>>,
+    index: NgramIndex,
+    /// First letters of the keys, to look up typos.
+    heads: HeadIndex,
+    /// Conditions under which the children are shown, by position.
+    conditions: Vec<Option<Predicate>>,
+    /// Matches of the children built on first use, by position.
+    lazy: Vec<Option<LazyMatch>>,
     max_results: usize,
+    ranking: Ranking,
+    journal: Option<Journal>,
+    history: Option<History>,
+    runner: Arc<dyn CommandRunner>,
+    stdout: bool,
+    notifier: Option<Arc<dyn Notifier>>,
+}
+
+/// Match of a child that is built when first needed, see `with_lazy_child`.
+struct LazyMatch {
+    build: Box<dyn Fn() -> Option<SimpleMatch>>,
+    built: OnceLock<Option<SimpleMatch>>,
 }
 
 impl StaticMatcher {
     pub fn new() -> Self {
         StaticMatcher {
             children: Vec::new(),
+            keys: Vec::new(),
+            exact: HashMap::new(),
+            index: NgramIndex::new(),
+            heads: HeadIndex::new(),
+            conditions: Vec::new(),
+            lazy: Vec::new(),
             max_results: 1000,
+            ranking: Ranking::default(),
+            journal: None,
+            history: None,
+            runner: Arc::new(SystemRunner),
+            stdout: false,
+            notifier: None,
         }
     }
 
-    pub fn with_child(mut self, key: SimpleMatch, matcher: Box<dyn Matcher>) -> Self {
+    pub fn with_child(self, key: SimpleMatch, matcher: Box<dyn Matcher>) -> Self {
+        self.add_child(key, matcher, None, None)
+    }
+
+    /// Adds a child that is only shown while `predicate` holds. The predicate is
+    /// checked once, and only if the child would otherwise be part of the results.
+    pub fn with_child_if(
+        self,
+        predicate: Predicate,
+        key: SimpleMatch,
+        matcher: Box<dyn Matcher>,
+    ) -> Self {
+        self.add_child(key, matcher, Some(predicate), None)
+    }
+
+    /// Adds a child whose match is built by `build` instead of being fixed, and
+    /// which is only shown if `build` returns one. The input is matched against
+    /// `key`, and like predicates, `build` runs once and only if needed.
+    pub fn with_lazy_child(
+        self,
+        key: &str,
+        build: impl Fn() -> Option<SimpleMatch> + 'static,
+        matcher: Box<dyn Matcher>,
+    ) -> Self {
+        let lazy = LazyMatch {
+            build: Box::new(build),
+            built: OnceLock::new(),
+        };
+        self.add_child(SimpleMatch::new(key, "", ""), matcher, None, Some(lazy))
+    }
+
+    fn add_child(
+        mut self,
+        key: SimpleMatch,
+        matcher: Box<dyn Matcher>,
+        condition: Option<Predicate>,
+        lazy: Option<LazyMatch>,
+    ) -> Self {
+        let id = self.children.len();
+        let normalized = Key::new(&key.title);
+        self.index.insert(id, &normalized.normalized);
+        self.heads.insert(id, &normalized.normalized);
+        self.exact
+            .entry(normalized.normalized.clone())
+            .or_default()
+            .push(id);
+        self.keys.push(normalized);
+        self.conditions.push(condition);
+        self.lazy.push(lazy);
         self.children.push((key, matcher));
         self
     }
@@ -26,6 +124,56 @@ impl StaticMatcher {
         self.max_results = max;
         self
     }
+
+    /// Also lists children whose key is a few typos away from the input,
+    /// after the children containing it.
+    pub fn with_typo_tolerance(mut self, typos: bool) -> Self {
+        self.ranking = self.ranking.with_typos(typos);
+        self
+    }
+
+    /// Also lists children whose key has words starting with the letters of
+    /// the input, e.g. "ape" for "anyrun-plugins-experimental".
+    pub fn with_acronyms(mut self, acronyms: bool) -> Self {
+        self.ranking = self.ranking.with_acronyms(acronyms);
+        self
+    }
+
+    /// Runner used for `exec|<command>` actions.
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Prints the value of copy actions to stdout instead of copying it, and
+    /// skips actions with side effects, for anyrun's dmenu-style usage. See
+    /// `Action::into_stdout`.
+    pub fn with_stdout(mut self, stdout: bool) -> Self {
+        self.stdout = stdout;
+        self
+    }
+
+    /// Notifies whether commands and undos of selected matches succeeded, since
+    /// anyrun closes without any feedback otherwise.
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Records the matches whose commands ran successfully under the query of
+    /// the history, and offers them again by that query for an empty one,
+    /// together with an action clearing them.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Records changes that executed commands make to the journal's tracked
+    /// files, and enables `undo|<n>` actions.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
 }
 
 impl Matcher for StaticMatcher {
@@ -37,50 +185,602 @@ impl Matcher for StaticMatcher {
             text = input.remove(0);
         }
 
+        let text = normalize(text);
+        let history = match &self.history {
+            Some(history) if text.is_empty() => self.history_matches(history),
+            _ => Vec::new(),
+        };
+
         // If any child matches perfectly, return its matches
-        for (key, matcher) in &self.children {
-            if key.title.to_lowercase() == text.to_lowercase() {
-                let child_matches = matcher.get_matches(input);
-                if !child_matches.is_empty() {
-                    return child_matches;
-                }
-                return vec![key.clone()];
+        let mut exact = self.exact.get(&text).into_iter().flatten();
+        if let Some(&id) = exact.find(|&&id| self.is_shown(id)) {
+            let child_matches = self.children[id].1.get_matches(input);
+            if !child_matches.is_empty() {
+                return child_matches;
             }
+            return self.key_match(id).into_iter().collect();
         }
 
-        // Otherwise, match the child keys that contain the text
-        let mut matches = Vec::new();
-        for (key, _matcher) in &self.children {
-            if key.title.to_lowercase().contains(&text.to_lowercase()) {
-                matches.push(key.clone());
+        // Otherwise, match the child keys best matches first: substrings, then
+        // acronyms, then typos by distance, each in the order of the children.
+        // Each kind is only looked for while there is room left for it, and
+        // substrings and typos are looked up in the indexes.
+        let input = Key::new(&text);
+        let all = || 0..self.children.len();
+        let mut ids = Vec::new();
+        let substrings: Box<dyn Iterator<Item = usize>> = match self.index.candidates(&text) {
+            Some(ids) => Box::new(ids),
+            None => Box::new(all()),
+        };
+        self.extend_shown(
+            &mut ids,
+            substrings.filter(|&id| self.keys[id].contains(&input)),
+        );
+        if self.ranking.acronyms {
+            self.extend_shown(
+                &mut ids,
+                all().filter(|&id| {
+                    let key = &self.keys[id];
+                    key.is_acronym(&input) && !key.contains(&input)
+                }),
+            );
+        }
+        let max = max_typos(text.chars().count());
+        if self.ranking.typos && max > 0 && ids.len() < self.max_results {
+            // Once there are enough keys with some typos, later keys need fewer
+            // to rank before them. A key without typos contains the text, so
+            // none rank before keys with a single typo.
+            let room = self.max_results - ids.len();
+            let mut typos = Vec::new();
+            let mut counts = vec![0; max + 1];
+            let mut limit = max;
+            // Keys often share their beginning, like the names of emoji or man
+            // pages, and a distance within a limit holds under a lower one
+            let mut distances = HashMap::new();
+            for id in self.heads.candidates(&text, max) {
+                let key = &self.keys[id];
+                let distance = *distances
+                    .entry(key.typo_prefix(&input))
+                    .or_insert_with(|| key.typo_distance(&input, limit));
+                let Some(distance) = distance.filter(|&distance| distance <= limit) else {
+                    continue;
+                };
+                let better =
+                    key.contains(&input) || self.ranking.acronyms && key.is_acronym(&input);
+                if better || !self.is_shown(id) {
+                    continue;
+                }
+                typos.push((distance, id));
+                counts[distance] += 1;
+                while limit > 0 && counts[..=limit].iter().sum::<usize>() >= room {
+                    limit -= 1;
+                }
+                if limit == 0 {
+                    break;
+                }
             }
+            typos.sort_by_key(|&(distance, _)| distance);
+            ids.extend(typos.into_iter().take(room).map(|(_, id)| id));
         }
-        matches.truncate(self.max_results);
-        matches
+        let matches = ids.into_iter().filter_map(|id| self.key_match(id));
+        history.into_iter().chain(matches).collect()
     }
 
     fn handle(&self, selection: SimpleMatch) -> HandleResult {
-        let description = selection.description.clone();
-        if description.starts_with("exec|") {
-            let command = description.trim_start_matches("exec|");
-            println!("Executing command: {}", command);
-            let output = Command::new("sh").arg("-c").arg(command).output();
+        let Some(mut action) = Action::parse(&selection.description) else {
+            return HandleResult::Refresh(false);
+        };
+        if self.stdout {
+            let Some(stdout) = action.into_stdout() else {
+                eprintln!("Nothing to print for '{}'", selection.title);
+                return HandleResult::Refresh(false);
+            };
+            action = stdout;
+        }
+
+        let notify = action.has_side_effects();
+        let repeatable = notify && matches!(action, Action::Exec(_) | Action::Sequence { .. });
+        let result = self.perform(action);
+        if repeatable
+            && result.is_ok()
+            && let Some(history) = &self.history
+            && let Err(e) = history.record(&selection)
+        {
+            eprintln!("Error recording '{}' in history: {}", selection.title, e);
+        }
+        if notify && let Some(notifier) = &self.notifier {
+            let (summary, body) = match &result {
+                Ok(_) => (selection.title.clone(), "Done".to_string()),
+                Err(e) => (format!("Failed: {}", selection.title), e.clone()),
+            };
+            if let Err(e) = notifier.notify(&summary, &body) {
+                eprintln!("{}", e);
+            }
+        }
 
-            if let Err(e) = output {
+        match result {
+            Ok(result) => result,
+            Err(e) => {
                 // TODO: Find a good way to report errors, perhaps bubbling up to the UI.
-                eprintln!("Error executing command '{}': {}", command, e);
-                return HandleResult::Refresh(false);
+                eprintln!("{}", e);
+                HandleResult::Refresh(false)
+            }
+        }
+    }
+}
+
+impl StaticMatcher {
+    fn is_shown(&self, id: usize) -> bool {
+        self.conditions[id]
+            .as_ref()
+            .is_none_or(|predicate| predicate.holds())
+            && self.key_match(id).is_some()
+    }
+
+    /// Adds the shown children among `candidates` to `ids`, up to `max_results`.
+    /// Candidates are only asked for while there is room, since finding the
+    /// next one may take a long scan.
+    fn extend_shown(&self, ids: &mut Vec<usize>, mut candidates: impl Iterator<Item = usize>) {
+        while ids.len() < self.max_results {
+            let Some(id) = candidates.next() else {
+                break;
+            };
+            if self.is_shown(id) {
+                ids.push(id);
             }
+        }
+    }
+
+    /// Whether a recorded `query` can be offered, which it can't if all the
+    /// children its first word leads to are hidden.
+    fn is_recallable(&self, query: &str) -> bool {
+        let text = normalize(query.split_whitespace().next().unwrap_or_default());
+        let input = Key::new(&text);
+        let ids = match self.exact.get(&text) {
+            Some(ids) => ids.clone(),
+            None => (0..self.children.len())
+                .filter(|&id| self.ranking.rank_key(&self.keys[id], &input).is_some())
+                .collect(),
+        };
+        ids.is_empty() || ids.into_iter().any(|id| self.is_shown(id))
+    }
+
+    /// Match listed for the child at `id`, if it has one.
+    fn key_match(&self, id: usize) -> Option<SimpleMatch> {
+        match &self.lazy[id] {
+            Some(lazy) => lazy.built.get_or_init(|| (lazy.build)()).clone(),
+            None => Some(self.children[id].0.clone()),
+        }
+    }
 
-            return HandleResult::Close;
+    /// Recorded queries, leaving out those for children that are not shown
+    /// right now, like stopping while nothing is tracked.
+    fn history_matches(&self, history: &History) -> Vec<SimpleMatch> {
+        let mut matches = history
+            .entries()
+            .into_iter()
+            .filter(|entry| self.is_recallable(entry.recalled_query()))
+            .take(HISTORY_SHOWN)
+            .map(|entry| {
+                let query = entry.recalled_query();
+                SimpleMatch::new(query, "document-open-recent", &entry.description)
+            })
+            .collect::<Vec<_>>();
+        if !matches.is_empty() {
+            let clear = Action::ClearHistory.to_string();
+            matches.push(SimpleMatch::new(
+                "clear history",
+                "edit-clear-history",
+                &clear,
+            ));
         }
+        matches
+    }
 
-        if description.starts_with("copy|") {
-            let to_copy = description.trim_start_matches("copy|");
-            println!("Copying to clipboard: {}", to_copy);
-            return HandleResult::Copy(to_copy.as_bytes().into());
+    fn perform(&self, action: Action) -> Result<HandleResult, String> {
+        match action {
+            Action::Exec(command) => self.exec(&command),
+            Action::Copy(to_copy) => {
+                println!("Copying to clipboard: {}", to_copy);
+                Ok(HandleResult::Copy(to_copy.as_bytes().into()))
+            }
+            Action::Stdout(output) => Ok(HandleResult::Stdout(output.as_bytes().into())),
+            Action::Undo(n) => self.undo(n),
+            Action::ClearHistory => {
+                let history = self.history.as_ref().ok_or("No history to clear")?;
+                history.clear()?;
+                Ok(HandleResult::Refresh(false))
+            }
+            Action::Sequence {
+                actions,
+                stop_on_failure,
+            } => self.sequence(actions, stop_on_failure),
         }
+    }
+
+    fn exec(&self, command: &str) -> Result<HandleResult, String> {
+        println!("Executing command: {}", command);
+        let snapshot = self.journal.as_ref().map(|journal| journal.snapshot());
+        let output = self
+            .runner
+            .execute(command)
+            .map_err(|e| format!("Error executing command '{}': {}", command, e))?;
+
+        if let (Some(journal), Some(snapshot)) = (&self.journal, snapshot)
+            && let Err(e) = journal.record_changes(snapshot)
+        {
+            eprintln!("Error recording changes of '{}': {}", command, e);
+        }
+
+        if !output.status.success() {
+            return Err(format!(
+                "Command '{}' failed with {}: {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(HandleResult::Close)
+    }
+
+    fn undo(&self, n: usize) -> Result<HandleResult, String> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or("Nothing to undo without a journal")?;
+        let entry = journal
+            .undo(n)
+            .map_err(|e| format!("Error undoing change #{}: {}", n, e))?;
+        println!(
+            "Undid change to {}: {}",
+            entry.file.display(),
+            entry.summary()
+        );
+        Ok(HandleResult::Close)
+    }
+
+    /// Performs `actions` in order and returns the result of the last one.
+    fn sequence(
+        &self,
+        actions: Vec<Action>,
+        stop_on_failure: bool,
+    ) -> Result<HandleResult, String> {
+        let mut result = Ok(HandleResult::Close);
+        for action in actions {
+            if let Err(e) = &result {
+                if stop_on_failure {
+                    break;
+                }
+                eprintln!("{}", e);
+            }
+            result = self.perform(action);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoopMatcher;
+    use crate::handler_display::DisplayHandler;
+    use crate::notifier::CommandNotifier;
+    use crate::testing::{
+        Harness, assert_closed, assert_copied, assert_refreshed, assert_stdout, assert_titles,
+        select,
+    };
+
+    fn matcher() -> StaticMatcher {
+        StaticMatcher::new()
+            .with_child(
+                SimpleMatch::new("start", "", ""),
+                Box::new(DisplayHandler::new("started", "", "exec|watson start")),
+            )
+            .with_child(
+                SimpleMatch::new("stop", "", "exec|watson stop"),
+                Box::new(NoopMatcher),
+            )
+            .with_child(
+                SimpleMatch::new("Status", "", "copy|status"),
+                Box::new(NoopMatcher),
+            )
+    }
+
+    #[test]
+    fn test_filters_children_by_substring() {
+        let matcher = matcher();
+        assert_titles(&matcher, "", &["start", "stop", "Status"]);
+        assert_titles(&matcher, "st", &["start", "stop", "Status"]);
+        assert_titles(&matcher, "ta", &["start", "Status"]);
+        assert_titles(&matcher, "xyz", &[]);
+        assert_titles(&matcher, "tatu", &["Status"]);
+        assert_titles(&matcher.with_max_results(1), "st", &["start"]);
+    }
+
+    #[test]
+    fn test_ignores_diacritics_and_width() {
+        let matcher = StaticMatcher::new()
+            .with_child(SimpleMatch::new("Café", "", ""), Box::new(NoopMatcher))
+            .with_child(
+                SimpleMatch::new("ｐｒｏｊｅｃｔ", "", ""),
+                Box::new(NoopMatcher),
+            );
+        assert_titles(&matcher, "cafe", &["Café"]);
+        assert_titles(&matcher, "proj", &["ｐｒｏｊｅｃｔ"]);
+    }
+
+    #[test]
+    fn test_typo_tolerance() {
+        let matcher =
+            matcher().with_child(SimpleMatch::new("restart", "", ""), Box::new(NoopMatcher));
+        assert_titles(&matcher, "strat", &[]);
+
+        let matcher = matcher.with_typo_tolerance(true);
+        assert_titles(&matcher, "strat", &["start", "Status"]);
+        assert_titles(&matcher, "start", &["started"]);
+        // Typos rank after substrings, regardless of the order of the children.
+        assert_titles(&matcher, "star", &["start", "restart", "Status"]);
+    }
+
+    #[test]
+    fn test_acronyms() {
+        let matcher = matcher()
+            .with_child(
+                SimpleMatch::new("anyrun-plugins-experimental", "", ""),
+                Box::new(NoopMatcher),
+            )
+            .with_child(SimpleMatch::new("tape", "", ""), Box::new(NoopMatcher));
+        assert_titles(&matcher, "ape", &["tape"]);
+
+        let matcher = matcher.with_acronyms(true);
+        assert_titles(&matcher, "ape", &["tape", "anyrun-plugins-experimental"]);
+        assert_titles(&matcher, "sta", &["start", "Status"]);
+    }
+
+    #[test]
+    fn test_max_results_by_rank() {
+        let titles = ["rocekt x", "rokcte", "rokcte 2", "rocekt", "rocket launch"];
+        let matcher = titles
+            .iter()
+            .fold(StaticMatcher::new(), |matcher, title| {
+                matcher.with_child(SimpleMatch::new(title, "", ""), Box::new(NoopMatcher))
+            })
+            .with_child_if(
+                Predicate::new(|| false),
+                SimpleMatch::new("rocke", "", ""),
+                Box::new(NoopMatcher),
+            )
+            .with_typo_tolerance(true)
+            .with_acronyms(true);
+
+        // Keys with fewer typos rank first even when found last, and hidden
+        // keys take no room
+        let matcher = matcher.with_max_results(3);
+        assert_titles(&matcher, "rocket", &["rocket launch", "rocekt x", "rocekt"]);
+        let matcher = matcher.with_max_results(2);
+        assert_titles(&matcher, "rocket", &["rocket launch", "rocekt x"]);
+        assert_titles(&matcher, "rokct", &["rokcte", "rokcte 2"]);
+        assert_titles(&matcher, "rx", &["rocekt x"]);
+    }
+
+    #[test]
+    fn test_conditional_children() {
+        let harness = Harness::new()
+            .with_command("watson status", "")
+            .with_command_output("test -e", 1, "");
+        let runner = harness.context().runner;
+        let matcher = StaticMatcher::new()
+            .with_child_if(
+                Predicate::command_succeeds(runner.clone(), "watson status"),
+                SimpleMatch::new("stop", "", ""),
+                Box::new(NoopMatcher),
+            )
+            .with_child_if(
+                Predicate::command_succeeds(runner, "test -e todos.txt"),
+                SimpleMatch::new("todos", "", ""),
+                Box::new(NoopMatcher),
+            )
+            .with_child(SimpleMatch::new("start", "", ""), Box::new(NoopMatcher));
+
+        // Predicates of children that can't match are never checked
+        assert_titles(&matcher, "star", &["start"]);
+        assert!(harness.calls().is_empty());
+
+        assert_titles(&matcher, "", &["stop", "start"]);
+        assert_titles(&matcher, "todos", &[]);
+        assert_eq!(harness.calls(), ["watson status", "test -e todos.txt"]);
+    }
+
+    #[test]
+    fn test_lazy_children() {
+        let harness =
+            Harness::new().with_command("watson status", "Project anyrun started 3 hours ago");
+        let runner = harness.context().runner;
+        let matcher = StaticMatcher::new()
+            .with_lazy_child(
+                "stop",
+                move || {
+                    let status = runner.shell("watson status").ok()?.stdout;
+                    let status = String::from_utf8(status).ok()?;
+                    let title = format!("stop {}", status.split_whitespace().nth(1)?);
+                    Some(SimpleMatch::new(&title, "", "exec|watson stop"))
+                },
+                Box::new(NoopMatcher),
+            )
+            .with_lazy_child("todos", || None, Box::new(NoopMatcher))
+            .with_child(SimpleMatch::new("start", "", ""), Box::new(NoopMatcher));
+
+        // Matches of children that can't match are never built
+        assert_titles(&matcher, "star", &["start"]);
+        assert!(harness.calls().is_empty());
+
+        assert_titles(&matcher, "", &["stop anyrun", "start"]);
+        assert_titles(&matcher, "stop", &["stop anyrun"]);
+        assert_titles(&matcher, "todos", &[]);
+        assert_eq!(harness.calls(), ["watson status"]);
+    }
+
+    #[test]
+    fn test_exact_key_delegates_to_child() {
+        let matcher = matcher();
+        assert_titles(&matcher, "start", &["started"]);
+        assert_titles(&matcher, "start anything", &["started"]);
+        // Children without matches fall back to the key itself.
+        assert_titles(&matcher, "STOP", &["stop"]);
+    }
+
+    #[test]
+    fn test_handle_actions() {
+        let harness = Harness::new().with_command("watson stop", "");
+        let matcher = matcher().with_runner(harness.context().runner);
+
+        assert_closed(&select(&matcher, "stop", "stop"));
+        harness.assert_ran("watson stop");
+
+        assert_copied(&select(&matcher, "status", "Status"), "status");
+        assert_refreshed(&matcher.handle(SimpleMatch::new("plain", "", "")));
+    }
+
+    #[test]
+    fn test_handle_stdout() {
+        let harness = Harness::new();
+        let matcher = matcher()
+            .with_runner(harness.context().runner)
+            .with_stdout(true);
+
+        // Commands are neither run nor printed
+        assert_refreshed(&select(&matcher, "stop", "stop"));
+        assert_stdout(&select(&matcher, "status", "Status"), "status");
+        let print = SimpleMatch::new("print", "", "stdout|anyrun");
+        assert_stdout(&matcher.handle(print), "anyrun");
+        assert!(harness.calls().is_empty());
+    }
+
+    #[test]
+    fn test_handle_sequence() {
+        let harness = Harness::new()
+            .with_command("watson start", "")
+            .with_command_output("false", 1, "");
+        let matcher = matcher().with_runner(harness.context().runner);
+        let handle = |action: Action| matcher.handle(SimpleMatch::new("", "", &action.to_string()));
+
+        let start = Action::Exec("watson start anyrun".into());
+        let fail = Action::Exec("false".into());
+        let copy = Action::Copy("anyrun".into());
+
+        assert_copied(
+            &handle(Action::sequence(vec![start.clone(), copy.clone()], true)),
+            "anyrun",
+        );
+        assert_closed(&handle(Action::sequence(
+            vec![copy.clone(), start.clone()],
+            true,
+        )));
+
+        // The result of the last action is returned, failing or not.
+        assert_copied(
+            &handle(Action::sequence(vec![fail.clone(), copy.clone()], false)),
+            "anyrun",
+        );
+        assert_refreshed(&handle(Action::sequence(
+            vec![copy.clone(), fail.clone()],
+            false,
+        )));
+
+        // Stopping at the first failure skips the remaining actions.
+        let before = harness.calls().len();
+        assert_refreshed(&handle(Action::sequence(vec![fail, start], true)));
+        assert_eq!(harness.calls()[before..], ["false"]);
+    }
+
+    #[test]
+    fn test_handle_notifies() {
+        let harness = Harness::new()
+            .with_command("watson stop", "")
+            .with_command("notify", "");
+        let runner = harness.context().runner;
+        let matcher = matcher()
+            .with_runner(runner.clone())
+            .with_notifier(Arc::new(CommandNotifier::new(runner, "notify")));
+
+        assert_closed(&select(&matcher, "stop", "stop"));
+        assert_copied(&select(&matcher, "status", "Status"), "status");
+        assert_eq!(harness.calls(), ["watson stop", "notify 'stop' 'Done'"]);
+
+        assert_refreshed(&matcher.handle(SimpleMatch::new("fail", "", "exec|false")));
+        harness.assert_ran("notify 'Failed: fail' 'Error executing command");
+    }
+
+    #[test]
+    fn test_history() {
+        let harness = Harness::new().with_command("watson stop", "");
+        let context = harness.context();
+        let matcher = || {
+            matcher()
+                .with_runner(context.runner.clone())
+                .with_history(History::for_plugin(&context, "test").unwrap())
+        };
+        assert_titles(&matcher(), "", &["start", "stop", "Status"]);
+
+        assert_closed(&select(&matcher(), "stop", "stop"));
+        assert_copied(&select(&matcher(), "status", "Status"), "status");
+        assert_titles(
+            &matcher(),
+            "",
+            &["stop", "clear history", "start", "stop", "Status"],
+        );
+        assert_titles(&matcher(), "sto", &["stop"]);
+
+        assert_refreshed(&select(&matcher(), "", "clear history"));
+        assert_titles(&matcher(), "", &["start", "stop", "Status"]);
+    }
+
+    #[test]
+    fn test_history_recalls_queries() {
+        let harness = Harness::new()
+            .with_command("watson start", "")
+            .with_command("watson stop", "");
+        let context = harness.context();
+        let matcher = |query: &str, tracking: bool| {
+            let history = History::for_plugin(&context, "test").unwrap();
+            matcher()
+                .with_runner(context.runner.clone())
+                .with_history(history.with_query(query))
+                .with_child_if(
+                    Predicate::new(move || tracking),
+                    SimpleMatch::new("pause", "", "exec|watson stop"),
+                    Box::new(NoopMatcher),
+                )
+        };
+
+        assert_closed(&select(&matcher("start", true), "start", "started"));
+        assert_closed(&select(&matcher("pau", true), "pau", "pause"));
+        assert_titles(
+            &matcher("", true),
+            "",
+            &[
+                "pau",
+                "start",
+                "clear history",
+                "start",
+                "stop",
+                "Status",
+                "pause",
+            ],
+        );
+
+        // Stopping is not offered again while it isn't possible
+        assert_titles(
+            &matcher("", false),
+            "",
+            &["start", "clear history", "start", "stop", "Status"],
+        );
+    }
 
-        HandleResult::Refresh(false)
+    #[test]
+    fn test_handle_missing_command() {
+        let harness = Harness::new();
+        let matcher = matcher().with_runner(harness.context().runner);
+        assert_refreshed(&select(&matcher, "stop", "stop"));
     }
 }
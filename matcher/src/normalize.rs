@@ -0,0 +1,40 @@
+//! Text normalization used whenever matchers compare user input with candidates.
+//!
+//! Both sides are decomposed into their compatibility form (NFKD), stripped of
+//! combining marks and case folded, so that "cafe" finds "Café" and full-width
+//! "ａｎｙｒｕｎ" finds "anyrun".
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+pub fn normalize(text: &str) -> String {
+    text.nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether `haystack` contains `needle` once both are normalized.
+pub fn contains(haystack: &str, needle: &str) -> bool {
+    normalize(haystack).contains(&normalize(needle))
+}
+
+/// Whether `a` and `b` are equal once normalized.
+pub fn eq(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Café"), "cafe");
+        assert_eq!(normalize("Cafe\u{301}"), "cafe");
+        assert_eq!(normalize("ＡｎｙＲｕｎ"), "anyrun");
+        assert_eq!(normalize("Ångström ﬁx"), "angstrom fix");
+
+        assert!(contains("Buy Café beans", "cafe"));
+        assert!(eq("ÜBER", "uber"));
+        assert!(!contains("cafe", "café au lait"));
+    }
+}
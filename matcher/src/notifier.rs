@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crate::runner::{CommandRunner, quote};
+
+/// Notifier command used unless a plugin configures another one. It sends the
+/// notification over the freedesktop notifications D-Bus interface.
+pub const DEFAULT_NOTIFIER: &str = "notify-send";
+
+/// Sends desktop notifications, e.g. to confirm that a selected command ran.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String>;
+}
+
+/// Notifier that runs a command with the summary and body as its last two
+/// arguments, like `notify-send <summary> <body>`.
+pub struct CommandNotifier {
+    runner: Arc<dyn CommandRunner>,
+    command: String,
+}
+
+impl CommandNotifier {
+    /// `command` may contain arguments of its own, e.g. `notify-send -a anyrun`.
+    pub fn new(runner: Arc<dyn CommandRunner>, command: &str) -> Self {
+        CommandNotifier {
+            runner,
+            command: command.to_string(),
+        }
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        let command = format!("{} {} {}", self.command, quote(summary), quote(body));
+        let output = self
+            .runner
+            .execute(&command)
+            .map_err(|e| format!("Error running notifier '{}': {}", self.command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Notifier '{}' failed with {}",
+                self.command, output.status
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn test_command_notifier() {
+        let harness = Harness::new().with_command("notify", "");
+        let notifier = CommandNotifier::new(harness.context().runner, "notify -a anyrun");
+
+        notifier.notify("stop anyrun", "it's done").unwrap();
+        assert_eq!(
+            harness.calls(),
+            ["notify -a anyrun 'stop anyrun' 'it'\\''s done'"]
+        );
+
+        let missing = CommandNotifier::new(harness.context().runner, "missing-notifier");
+        assert!(missing.notify("stop anyrun", "").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Conditions under which a `StaticMatcher` child is shown.
+//!
+//! Predicates are only checked when their child could be part of the results,
+//! and at most once, so expensive checks like running a command don't slow down
+//! queries that never reach them.
+use std::ops::Not;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use chrono::NaiveTime;
+
+use crate::{clock::Clock, runner::CommandRunner};
+
+pub struct Predicate {
+    check: Box<dyn Fn() -> bool>,
+    result: OnceLock<bool>,
+}
+
+impl Predicate {
+    pub fn new(check: impl Fn() -> bool + 'static) -> Self {
+        Predicate {
+            check: Box::new(check),
+            result: OnceLock::new(),
+        }
+    }
+
+    pub fn file_exists(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Predicate::new(move || path.exists())
+    }
+
+    /// Holds if `command` exits successfully when run through `sh -c`.
+    pub fn command_succeeds(runner: Arc<dyn CommandRunner>, command: &str) -> Self {
+        let command = command.to_string();
+        Predicate::new(move || {
+            runner
+                .shell(&command)
+                .is_ok_and(|output| output.status.success())
+        })
+    }
+
+    /// Holds from `from` until `to`, wrapping around midnight if `to` is earlier.
+    pub fn time_between(clock: Arc<dyn Clock>, from: NaiveTime, to: NaiveTime) -> Self {
+        Predicate::new(move || {
+            let now = clock.now().time();
+            if from <= to {
+                from <= now && now < to
+            } else {
+                from <= now || now < to
+            }
+        })
+    }
+
+    /// Holds if the environment variable `name` is set and not empty.
+    pub fn env_var(name: &str) -> Self {
+        let name = name.to_string();
+        Predicate::new(move || std::env::var_os(&name).is_some_and(|value| !value.is_empty()))
+    }
+
+    /// Whether the condition holds, checked on the first call only.
+    pub fn holds(&self) -> bool {
+        *self.result.get_or_init(|| (self.check)())
+    }
+}
+
+impl Not for Predicate {
+    type Output = Predicate;
+
+    fn not(self) -> Predicate {
+        Predicate::new(move || !self.holds())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[test]
+    fn test_predicates() {
+        let harness = Harness::new()
+            .with_time("2025-11-06 23:30:00")
+            .with_command("true", "")
+            .with_command_output("false", 1, "");
+        let context = harness.context();
+        harness.write_home("notes.txt", "");
+
+        assert!(Predicate::file_exists(harness.root().home().join("notes.txt")).holds());
+        assert!(!Predicate::file_exists(harness.root().home().join("todos.txt")).holds());
+
+        assert!(Predicate::command_succeeds(context.runner.clone(), "true").holds());
+        assert!(!Predicate::command_succeeds(context.runner.clone(), "false").holds());
+        assert!(!Predicate::command_succeeds(context.runner.clone(), "missing").holds());
+
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let clock = context.clock.clone();
+        assert!(Predicate::time_between(clock.clone(), time(22, 0), time(6, 0)).holds());
+        assert!(!Predicate::time_between(clock.clone(), time(9, 0), time(17, 0)).holds());
+        assert!((!Predicate::time_between(clock, time(9, 0), time(17, 0))).holds());
+
+        assert!(Predicate::env_var("PATH").holds());
+        assert!(!Predicate::env_var("ANYRUN_PLUGINS_UNSET_VARIABLE").holds());
+    }
+
+    #[test]
+    fn test_checked_once() {
+        let harness = Harness::new().with_command("watson status", "");
+        let predicate = Predicate::command_succeeds(harness.context().runner, "watson status");
+        assert!(harness.calls().is_empty());
+
+        assert!(predicate.holds());
+        assert!(predicate.holds());
+        assert_eq!(harness.calls(), ["watson status"]);
+    }
+}
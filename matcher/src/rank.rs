@@ -0,0 +1,417 @@
+//! Ranking of candidates against the text typed by the user.
+//!
+//! Every comparison goes through `normalize`, so rankings ignore case and
+//! diacritics. Substring matches always rank first, the other kinds of
+//! matches are opt-in per matcher.
+use crate::normalize::normalize;
+
+/// How a candidate matched the input. Better matches compare lower.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The candidate contains the input.
+    Substring,
+    /// The input is made of the initials of consecutive words of the candidate,
+    /// each followed by more of its word, e.g. "ape", "api" or "anplex" for
+    /// "anyrun-plugins-experimental".
+    Acronym,
+    /// The candidate, or its beginning, is within this many typos of the input.
+    Typo(usize),
+}
+
+/// Which kinds of matches a matcher accepts besides substrings.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ranking {
+    pub typos: bool,
+    pub acronyms: bool,
+}
+
+impl Ranking {
+    pub fn with_typos(mut self, typos: bool) -> Self {
+        self.typos = typos;
+        self
+    }
+
+    pub fn with_acronyms(mut self, acronyms: bool) -> Self {
+        self.acronyms = acronyms;
+        self
+    }
+
+    /// Whether only substrings match, which an index can look up.
+    pub fn substrings_only(&self) -> bool {
+        !self.typos && !self.acronyms
+    }
+
+    /// How `candidate` matches `input`, if at all.
+    pub fn rank(&self, candidate: &str, input: &str) -> Option<MatchKind> {
+        self.rank_key(&Key::new(candidate), &Key::new(input))
+    }
+
+    /// Like `rank`, for a prepared candidate and input.
+    pub fn rank_key(&self, key: &Key, input: &Key) -> Option<MatchKind> {
+        if key.contains(input) {
+            return Some(MatchKind::Substring);
+        }
+        if self.acronyms && key.is_acronym(input) {
+            return Some(MatchKind::Acronym);
+        }
+        if self.typos {
+            return key
+                .typo_distance(input, max_typos(input.len))
+                .map(MatchKind::Typo);
+        }
+        None
+    }
+}
+
+/// A candidate normalized once, to be ranked against many inputs.
+#[derive(Clone, Debug)]
+pub struct Key {
+    pub normalized: String,
+    pub words: Vec<String>,
+    /// Number of characters of `normalized`.
+    len: usize,
+    /// Letters of the key and its words, to rule out most candidates before
+    /// comparing them with the input.
+    letters: u64,
+    /// Letters that start a word.
+    initials: u64,
+}
+
+impl Key {
+    pub fn new(text: &str) -> Self {
+        let normalized = normalize(text);
+        let words = words(text);
+        let letters = words
+            .iter()
+            .fold(letter_set(normalized.chars()), |letters, word| {
+                letters | letter_set(word.chars())
+            });
+        let initials = letter_set(words.iter().filter_map(|word| word.chars().next()));
+        Key {
+            len: normalized.chars().count(),
+            normalized,
+            words,
+            letters,
+            initials,
+        }
+    }
+
+    /// Whether the key contains `input`.
+    pub fn contains(&self, input: &Key) -> bool {
+        input.letters & !self.letters == 0 && self.normalized.contains(&input.normalized)
+    }
+
+    /// Whether `input` is an acronym of the key's words, see `MatchKind::Acronym`.
+    pub fn is_acronym(&self, input: &Key) -> bool {
+        let Some(first) = input.normalized.chars().next() else {
+            return false;
+        };
+        letter_set([first]) & !self.initials == 0
+            && input.letters & !self.letters == 0
+            && is_acronym(&self.words, &input.normalized)
+    }
+
+    /// Beginning of the key that `typo_distance` compares with `input`, which
+    /// is all the typos depend on.
+    pub fn typo_prefix(&self, input: &Key) -> &str {
+        let len = input.len + max_typos(input.len);
+        let end = self
+            .normalized
+            .char_indices()
+            .nth(len)
+            .map_or(self.normalized.len(), |(i, _)| i);
+        &self.normalized[..end]
+    }
+
+    /// Typos between `input` and the key or the beginning of it, if within
+    /// `max`, which is at most `max_typos` of the length of the input.
+    pub fn typo_distance(&self, input: &Key, max: usize) -> Option<usize> {
+        if max == 0 || self.len + max < input.len {
+            return None;
+        }
+
+        // Compare with prefixes around the length of the input, so that the rest
+        // of the candidate does not count against partially typed words. Each
+        // letter of the input missing from them takes a typo.
+        let prefix = self.normalized.chars().take(input.len + max);
+        let missing = input.letters & !letter_set(prefix.clone());
+        if missing.count_ones() as usize > max {
+            return None;
+        }
+
+        prefix_distance(prefix, &input.normalized, input.len, max)
+    }
+}
+
+/// Set of `chars`, each hashed to one of 64 bits. A letter missing from a set
+/// is missing from the text, while a letter in it may only share its bit.
+fn letter_set(chars: impl IntoIterator<Item = char>) -> u64 {
+    chars
+        .into_iter()
+        .fold(0, |letters, c| letters | 1 << (c as u32 % 64))
+}
+
+/// Normalized words of `text`, split at separators and camelCase humps.
+pub fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        let separator = !c.is_alphanumeric();
+        let hump = c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_numeric());
+        if (separator || hump) && !word.is_empty() {
+            words.push(normalize(&word));
+            word.clear();
+        }
+        if !separator {
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(normalize(&word));
+    }
+    words
+}
+
+/// Whether `input` is made of parts of consecutive `words`, starting at any of
+/// them and spanning at least two. Each part starts with the first letter of
+/// its word, and its other letters appear in the word in order.
+fn is_acronym(words: &[String], input: &str) -> bool {
+    fn matches(words: &[String], input: &str, spanned: usize) -> bool {
+        if input.is_empty() {
+            return spanned >= 2;
+        }
+        let Some((word, rest)) = words.split_first() else {
+            return false;
+        };
+        if word.chars().next() != input.chars().next() {
+            return false;
+        }
+        // Length of the longest part of the input found in the word, since any
+        // shorter part is found too
+        let mut letters = word.chars();
+        let longest = input
+            .chars()
+            .take_while(|&c| letters.any(|letter| letter == c))
+            .count();
+        // Try the longest part first, then shorter ones
+        (1..=longest).rev().any(|len| {
+            let end = input
+                .char_indices()
+                .nth(len)
+                .map_or(input.len(), |(i, _)| i);
+            matches(rest, &input[end..], spanned + 1)
+        })
+    }
+
+    !input.is_empty() && (0..words.len()).any(|start| matches(&words[start..], input, 0))
+}
+
+/// Number of typos tolerated in an input of `len` characters. Short inputs get
+/// none, since nearly every short word is a typo away from another.
+pub fn max_typos(len: usize) -> usize {
+    match len {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Smallest distance between `input`, of `len` characters, and a prefix of
+/// `candidate` that is at most `max` characters shorter or longer, if within
+/// `max`. All prefixes are compared at once, since the distance to each prefix
+/// is one row of the distance to the whole candidate.
+fn prefix_distance(
+    candidate: impl Iterator<Item = char>,
+    input: &str,
+    len: usize,
+    max: usize,
+) -> Option<usize> {
+    let shortest = len.saturating_sub(max);
+    let longest = len + max;
+
+    // Rows for the prefixes of length i - 2, i - 1 and i, each at i % 3. This
+    // runs for many candidates, so usual inputs don't allocate.
+    let width = len + 1;
+    let mut stack = [0; 3 * 32];
+    let mut heap = Vec::new();
+    let rows = if 3 * width <= stack.len() {
+        &mut stack[..3 * width]
+    } else {
+        heap.resize(3 * width, 0);
+        &mut heap[..]
+    };
+    for (j, cell) in rows[..width].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    let mut best = (shortest == 0).then_some(len);
+    let mut previous = None;
+    let mut previous_min = 0;
+    for (i, c) in (1..=longest).zip(candidate) {
+        let row = i % 3 * width;
+        let above = (i - 1) % 3 * width;
+        let twice_above = (i + 1) % 3 * width;
+        rows[row] = i;
+        let mut row_min = i;
+        let mut input_previous = None;
+        for (j, input_c) in (1..width).zip(input.chars()) {
+            let cost = usize::from(c != input_c);
+            let mut d = (rows[above + j] + 1)
+                .min(rows[row + j - 1] + 1)
+                .min(rows[above + j - 1] + cost);
+            if input_previous == Some(c) && previous == Some(input_c) {
+                d = d.min(rows[twice_above + j - 2] + 1);
+            }
+            rows[row + j] = d;
+            row_min = row_min.min(d);
+            input_previous = Some(input_c);
+        }
+        if i >= shortest {
+            let d = rows[row + len];
+            best = Some(best.map_or(d, |best: usize| best.min(d)));
+        }
+        // Rows only grow past two rows over `max`, as a transposition reaches
+        // back two rows
+        if row_min > max && previous_min > max {
+            break;
+        }
+        previous = Some(c);
+        previous_min = row_min;
+    }
+    best.filter(|&d| d <= max)
+}
+
+/// Whether `input` is a misspelling of the whole of `candidate`, e.g. a project name.
+pub fn is_typo_of(candidate: &str, input: &str) -> bool {
+    let candidate = normalize(candidate).chars().collect::<Vec<_>>();
+    let input = normalize(input).chars().collect::<Vec<_>>();
+    let max = max_typos(input.len());
+    max > 0 && distance(&candidate, &input, max).is_some_and(|d| d > 0)
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment) between `a` and `b`,
+/// or `None` as soon as it is known to exceed `max`.
+pub fn distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        let mut row_min = rows[i][0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = d;
+            row_min = row_min.min(d);
+        }
+        if row_min > max {
+            return None;
+        }
+    }
+
+    let d = rows[a.len()][b.len()];
+    (d <= max).then_some(d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance(&chars("anyrun"), &chars("anyrun"), 2), Some(0));
+        assert_eq!(distance(&chars("anyrun"), &chars("anyrn"), 2), Some(1));
+        assert_eq!(distance(&chars("anyrun"), &chars("anyurn"), 2), Some(1));
+        assert_eq!(distance(&chars("anyrun"), &chars("nayrnu"), 2), Some(2));
+        assert_eq!(distance(&chars("anyrun"), &chars("watson"), 2), None);
+    }
+
+    #[test]
+    fn test_prefix_distance() {
+        let pairs = [
+            ("anyrun-plugins", "anyrn-pl"),
+            ("anyrun", "nayrnu"),
+            ("rocket moon", "rokcte"),
+            ("ls", "lsx"),
+            ("watson", "anyrun"),
+        ];
+        for (candidate, input) in pairs {
+            // Same as the distance to the closest of the prefixes
+            let (c, i) = (chars(candidate), chars(input));
+            let expected = (i.len() - 2..=(i.len() + 2).min(c.len()))
+                .filter_map(|len| distance(&c[..len], &i, 2))
+                .min();
+            let actual = prefix_distance(candidate.chars(), input, i.len(), 2);
+            assert_eq!(actual, expected, "'{}' for '{}'", candidate, input);
+        }
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("anyrun-plugins_Experimental v2"),
+            ["anyrun", "plugins", "experimental", "v2"]
+        );
+        assert_eq!(words("myHTTPServer2Go"), ["my", "httpserver2", "go"]);
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranking = Ranking::default();
+        assert_eq!(ranking.rank("Café", "caf"), Some(MatchKind::Substring));
+        assert_eq!(ranking.rank("anyrun", "anyrn"), None);
+
+        let ranking = ranking.with_typos(true);
+        assert_eq!(ranking.rank("anyrun", "anyrn"), Some(MatchKind::Typo(1)));
+        // Partially typed words are compared with the beginning of the candidate.
+        assert_eq!(
+            ranking.rank("anyrun-plugins", "anyrn-pl"),
+            Some(MatchKind::Typo(1))
+        );
+        assert_eq!(ranking.rank("ls", "lx"), None);
+
+        let ranking = Ranking::default().with_acronyms(true);
+        assert_eq!(
+            ranking.rank("anyrun-plugins-experimental", "ape"),
+            Some(MatchKind::Acronym)
+        );
+        assert_eq!(
+            ranking.rank("anyrun_pluginsExperimental", "anplex"),
+            Some(MatchKind::Acronym)
+        );
+        assert_eq!(
+            ranking.rank("anyrun-plugins-experimental", "pex"),
+            Some(MatchKind::Acronym)
+        );
+        assert_eq!(
+            ranking.rank("anyrun-plugins-experimental", "api"),
+            Some(MatchKind::Acronym)
+        );
+        assert_eq!(ranking.rank("anyrun-plugins-experimental", "ae"), None);
+        assert_eq!(ranking.rank("anyrun-plugins-experimental", "aix"), None);
+        assert_eq!(ranking.rank("anyrun-plugins-experimental", "apx"), None);
+
+        assert!(is_typo_of("anyrun", "anyrn"));
+        assert!(!is_typo_of("anyrun", "anyrun"));
+        assert!(!is_typo_of("anyrun-plugins", "anyrn"));
+    }
+}
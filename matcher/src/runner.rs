@@ -0,0 +1,145 @@
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Arc, Mutex};
+
+/// Runs external programs on behalf of matchers.
+pub trait CommandRunner: Send + Sync {
+    /// Runs `program` with `args` and collects its output.
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output>;
+
+    /// Runs `command` through `sh -c`.
+    fn shell(&self, command: &str) -> io::Result<Output> {
+        self.run("sh", &["-c", command])
+    }
+
+    /// Runs `command` through `sh -c` as the action of a selected match.
+    /// Unlike `shell`, the command is expected to have side effects.
+    fn execute(&self, command: &str) -> io::Result<Output> {
+        self.shell(command)
+    }
+}
+
+/// Quotes `text` as a single shell word.
+pub fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Runner that spawns real processes.
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        Command::new(program).args(args).output()
+    }
+}
+
+/// Runner that runs queries through `inner` but only prints the commands of
+/// actions.
+pub struct DryRunRunner {
+    inner: Arc<dyn CommandRunner>,
+}
+
+impl DryRunRunner {
+    pub fn new(inner: Arc<dyn CommandRunner>) -> Self {
+        DryRunRunner { inner }
+    }
+}
+
+impl CommandRunner for DryRunRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        self.inner.run(program, args)
+    }
+
+    fn execute(&self, command: &str) -> io::Result<Output> {
+        eprintln!("Dry run, not executing: {}", command);
+        Ok(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+/// Runner that answers with canned outputs and records every command line.
+///
+/// Commands are matched by prefix against their command line, where
+/// `sh -c <command>` is recorded as just `<command>`. Commands without a canned
+/// output fail as if the program was not installed, unless they match a
+/// passthrough prefix and are run for real.
+#[derive(Default)]
+pub struct FakeRunner {
+    state: Mutex<FakeRunnerState>,
+}
+
+#[derive(Default)]
+struct FakeRunnerState {
+    outputs: Vec<(String, i32, String)>,
+    passthrough: Vec<String>,
+    calls: Vec<String>,
+}
+
+impl FakeRunner {
+    pub fn new() -> Self {
+        FakeRunner::default()
+    }
+
+    /// Answers commands starting with `prefix` with `stdout` and the exit `code`.
+    pub fn add_output(&self, prefix: &str, code: i32, stdout: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .outputs
+            .push((prefix.to_string(), code, stdout.to_string()));
+    }
+
+    /// Really runs commands starting with `prefix`.
+    pub fn add_passthrough(&self, prefix: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.passthrough.push(prefix.to_string());
+    }
+
+    /// Command lines run so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+}
+
+impl CommandRunner for FakeRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        let line = match (program, args) {
+            ("sh", ["-c", command]) => command.to_string(),
+            _ => std::iter::once(program)
+                .chain(args.iter().copied())
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(line.clone());
+
+        if state
+            .passthrough
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+        {
+            return SystemRunner.run(program, args);
+        }
+
+        let Some((_, code, stdout)) = state
+            .outputs
+            .iter()
+            .find(|(prefix, _, _)| line.starts_with(prefix))
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no fake output for '{}'", line),
+            ));
+        };
+
+        Ok(Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.clone().into_bytes(),
+            stderr: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,249 @@
+//! Fakes and assertion helpers for running matcher trees and plugins in-process.
+//!
+//! A `Harness` bundles a fake runner, a fake clock and a temporary filesystem
+//! root into a `Context` that plugins build their matchers from.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyrun_plugin::HandleResult;
+use tempfile::TempDir;
+
+use crate::clock::{FakeClock, Zone};
+use crate::context::Context;
+use crate::files::FileWriter;
+use crate::runner::{CommandRunner, DryRunRunner, FakeRunner};
+use crate::{Matcher, SimpleMatch};
+
+/// Time the fake clock starts at unless `Harness::with_time` is used.
+pub const DEFAULT_TIME: &str = "2025-11-06 17:00:00";
+
+/// Temporary directory standing in for the user's home and state directories.
+pub struct FakeRoot {
+    dir: TempDir,
+}
+
+impl FakeRoot {
+    pub fn new() -> Self {
+        let dir = TempDir::new().expect("failed to create fake root");
+        fs::create_dir_all(dir.path().join("home")).expect("failed to create fake home");
+        fs::create_dir_all(dir.path().join("state")).expect("failed to create fake state");
+        FakeRoot { dir }
+    }
+
+    pub fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    pub fn state(&self) -> PathBuf {
+        self.dir.path().join("state")
+    }
+}
+
+impl Default for FakeRoot {
+    fn default() -> Self {
+        FakeRoot::new()
+    }
+}
+
+/// Fake runner, clock and filesystem root for a test.
+pub struct Harness {
+    root: FakeRoot,
+    runner: Arc<FakeRunner>,
+    clock: Arc<FakeClock>,
+    dry_run: bool,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Harness {
+            root: FakeRoot::new(),
+            runner: Arc::new(FakeRunner::new()),
+            clock: Arc::new(FakeClock::at(DEFAULT_TIME)),
+            dry_run: false,
+        }
+    }
+
+    /// Context backed by the fakes of this harness.
+    pub fn context(&self) -> Context {
+        let runner: Arc<dyn CommandRunner> = if self.dry_run {
+            Arc::new(DryRunRunner::new(self.runner.clone()))
+        } else {
+            self.runner.clone()
+        };
+        Context {
+            runner,
+            clock: self.clock.clone(),
+            files: FileWriter::new(self.dry_run),
+            home_dir: Some(self.root.home()),
+            state_home: Some(self.root.state()),
+        }
+    }
+
+    /// Sets the fake clock to a time formatted as `%Y-%m-%d %H:%M:%S` in its
+    /// zone, UTC unless `with_zone` is used.
+    pub fn with_time(self, time: &str) -> Self {
+        self.clock.set(time);
+        self
+    }
+
+    /// Moves the fake clock to `zone`, keeping its local time.
+    pub fn with_zone(self, zone: chrono_tz::Tz) -> Self {
+        self.clock.set_zone(Zone::Named(zone));
+        self
+    }
+
+    /// Answers commands starting with `prefix` with `stdout` and a zero exit code.
+    pub fn with_command(self, prefix: &str, stdout: &str) -> Self {
+        self.with_command_output(prefix, 0, stdout)
+    }
+
+    pub fn with_command_output(self, prefix: &str, code: i32, stdout: &str) -> Self {
+        self.runner.add_output(prefix, code, stdout);
+        self
+    }
+
+    /// Skips the commands of selected matches and all file writes, like
+    /// `anyrun-plugin-cli --dry-run`.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Really runs commands starting with `prefix`, e.g. `sed` on a file in the fake root.
+    pub fn with_passthrough(self, prefix: &str) -> Self {
+        self.runner.add_passthrough(prefix);
+        self
+    }
+
+    pub fn root(&self) -> &FakeRoot {
+        &self.root
+    }
+
+    pub fn runner(&self) -> &FakeRunner {
+        &self.runner
+    }
+
+    pub fn clock(&self) -> &FakeClock {
+        &self.clock
+    }
+
+    /// Writes `content` to `path`, relative to the fake home directory.
+    pub fn write_home(&self, path: &str, content: &str) {
+        let path = self.root.home().join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create parent directory");
+        }
+        fs::write(&path, content).expect("failed to write file");
+    }
+
+    /// Reads `path`, relative to the fake home directory.
+    pub fn read_home(&self, path: &str) -> String {
+        read(&self.root.home().join(path))
+    }
+
+    /// Command lines run so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.runner.calls()
+    }
+
+    pub fn assert_ran(&self, prefix: &str) {
+        let calls = self.calls();
+        assert!(
+            calls.iter().any(|call| call.starts_with(prefix)),
+            "expected a command starting with '{}', ran {:?}",
+            prefix,
+            calls
+        );
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Harness::new()
+    }
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e))
+}
+
+/// Runs `query` through `matcher` the way plugins do after stripping their prefix.
+pub fn query(matcher: &dyn Matcher, query: &str) -> Vec<SimpleMatch> {
+    let query = query.trim().to_lowercase();
+    matcher.get_matches(query.split_ascii_whitespace().collect())
+}
+
+pub fn titles(matcher: &dyn Matcher, query: &str) -> Vec<String> {
+    self::query(matcher, query)
+        .into_iter()
+        .map(|m| m.title)
+        .collect()
+}
+
+pub fn assert_titles(matcher: &dyn Matcher, query: &str, expected: &[&str]) {
+    assert_eq!(
+        titles(matcher, query),
+        expected,
+        "titles for query '{}'",
+        query
+    );
+}
+
+/// Returns the match titled `title` for `query`, panicking if there is none.
+pub fn find(matcher: &dyn Matcher, query: &str, title: &str) -> SimpleMatch {
+    let matches = self::query(matcher, query);
+    let titles = matches.iter().map(|m| m.title.clone()).collect::<Vec<_>>();
+    matches
+        .into_iter()
+        .find(|m| m.title == title)
+        .unwrap_or_else(|| panic!("no match '{}' for query '{}' in {:?}", title, query, titles))
+}
+
+/// Selects the match titled `title` for `query` and returns the handler's result.
+pub fn select(matcher: &dyn Matcher, query: &str, title: &str) -> HandleResult {
+    matcher.handle(find(matcher, query, title))
+}
+
+pub fn describe(result: &HandleResult) -> String {
+    match result {
+        HandleResult::Close => "Close".to_string(),
+        HandleResult::Refresh(exit) => format!("Refresh({})", exit),
+        HandleResult::Copy(bytes) => format!("Copy({:?})", String::from_utf8_lossy(bytes)),
+        HandleResult::Stdout(bytes) => format!("Stdout({:?})", String::from_utf8_lossy(bytes)),
+    }
+}
+
+pub fn assert_closed(result: &HandleResult) {
+    assert!(
+        matches!(result, HandleResult::Close),
+        "expected Close, got {}",
+        describe(result)
+    );
+}
+
+pub fn assert_refreshed(result: &HandleResult) {
+    assert!(
+        matches!(result, HandleResult::Refresh(_)),
+        "expected Refresh, got {}",
+        describe(result)
+    );
+}
+
+pub fn assert_stdout(result: &HandleResult, expected: &str) {
+    assert!(
+        matches!(result, HandleResult::Stdout(bytes) if bytes.as_slice() == expected.as_bytes()),
+        "expected Stdout({:?}), got {}",
+        expected,
+        describe(result)
+    );
+}
+
+pub fn assert_copied(result: &HandleResult, expected: &str) {
+    assert!(
+        matches!(result, HandleResult::Copy(bytes) if bytes.as_slice() == expected.as_bytes()),
+        "expected Copy({:?}), got {}",
+        expected,
+        describe(result)
+    );
+}